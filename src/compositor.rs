@@ -0,0 +1,383 @@
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+
+use egui::TextureId;
+use egui_wgpu_backend::RenderPass;
+use image::RgbaImage;
+
+/// Width of the rows the covered flags are laid out in, one texel per tile.
+const COVERED_ROW: u32 = 256;
+/// Most tiles a tile map can have, the last id is left for pixels that belong to no tile.
+pub const MAX_TILES: usize = u16::MAX as usize;
+
+/// Which tile every pixel of the frame belongs to.
+#[derive(PartialEq, Clone, Debug)]
+pub struct TileMap {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major tile index of every pixel, `u16::MAX` where there is no tile. Shared with the
+    /// strategy, so handing the same map over again is cheap to send and to compare.
+    pub ids: Arc<[u16]>,
+}
+
+/// What the worker wants composited, picked up by the render loop. The worker only hands over
+/// what changed, so a reveal costs one texel per tile instead of a patch of the frame.
+#[derive(Default)]
+pub struct Composition {
+    active: bool,
+    image: Option<RgbaImage>,
+    tile_map: Option<TileMap>,
+    cover: Option<RgbaImage>,
+    covered: Option<Vec<bool>>,
+    /// Last tile map and cover handed over, a reset onto the same tiles only has to uncover them.
+    shown_tile_map: Option<TileMap>,
+    shown_cover: Option<RgbaImage>,
+    texture_id: Option<TextureId>,
+}
+
+impl Composition {
+    /// Sets the original image the tiles are cut from.
+    pub fn set_image(&mut self, image: RgbaImage) {
+        self.image = Some(image);
+    }
+
+    /// Starts compositing the frame out of `tile_map`, with `covered` tiles showing `cover`.
+    pub fn show_tiles(&mut self, tile_map: TileMap, cover: &RgbaImage, covered: &[bool]) {
+        if self.shown_tile_map.as_ref() != Some(&tile_map) {
+            self.shown_tile_map = Some(tile_map.clone());
+            self.tile_map = Some(tile_map);
+        }
+        if self.shown_cover.as_ref() != Some(cover) {
+            self.shown_cover = Some(cover.clone());
+            self.cover = Some(cover.clone());
+        }
+        self.set_covered(covered);
+        self.active = true;
+    }
+
+    pub fn set_covered(&mut self, covered: &[bool]) {
+        self.covered = Some(covered.to_vec());
+    }
+
+    /// Stops compositing, the frame comes from the worker's texture again.
+    pub fn hide(&mut self) {
+        self.active = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// The composited frame, `None` when the frame should be taken from the worker's texture.
+    pub fn texture_id(&self) -> Option<TextureId> {
+        self.texture_id.filter(|_| self.active)
+    }
+}
+
+struct Targets {
+    width: u32,
+    height: u32,
+    original: wgpu::Texture,
+    tile_ids: wgpu::Texture,
+    covered: wgpu::Texture,
+    cover: wgpu::Texture,
+    output: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Renders the frame of the tile reveal on the GPU: the original image is uploaded once and
+/// the covers are drawn from a map of tile ids and one covered flag per tile.
+pub struct Compositor {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    targets: Option<Targets>,
+    texture_id: Option<TextureId>,
+}
+
+impl Compositor {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("compositor_shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("compositor.wgsl").into()),
+        });
+
+        let texture_entry = |binding, sample_type| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("compositor_bind_group_layout"),
+            entries: &[
+                texture_entry(0, wgpu::TextureSampleType::Float { filterable: false }),
+                texture_entry(1, wgpu::TextureSampleType::Uint),
+                texture_entry(2, wgpu::TextureSampleType::Float { filterable: false }),
+                texture_entry(3, wgpu::TextureSampleType::Float { filterable: false }),
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("compositor_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("compositor_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            targets: None,
+            texture_id: None,
+        }
+    }
+
+    /// Uploads whatever changed in `composition` and renders the frame again if needed.
+    /// Has to run before `egui_rpass` draws, since it registers the frame with it.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        egui_rpass: &mut RenderPass,
+        composition: &Mutex<Composition>,
+    ) {
+        let mut composition = composition.lock().unwrap();
+        let (image, tile_map, cover, covered) = (
+            composition.image.take(),
+            composition.tile_map.take(),
+            composition.cover.take(),
+            composition.covered.take(),
+        );
+        if image.is_none() && tile_map.is_none() && cover.is_none() && covered.is_none() {
+            return;
+        }
+
+        if let Some(image) = image {
+            let (width, height) = image.dimensions();
+            if !matches!(&self.targets, Some(t) if (t.width, t.height) == (width, height)) {
+                self.create_targets(device, egui_rpass, width, height);
+                composition.shown_tile_map = None;
+                composition.shown_cover = None;
+            }
+            let targets = self.targets.as_ref().expect("Targets were just created.");
+            write_texture(queue, &targets.original, &image, width * 4, (width, height));
+        }
+        composition.texture_id = self.texture_id;
+        drop(composition);
+
+        let Some(targets) = &self.targets else {
+            return;
+        };
+
+        let size = (targets.width, targets.height);
+        if let Some(tile_map) = tile_map {
+            if (tile_map.width, tile_map.height) != size {
+                return;
+            }
+
+            let ids: Vec<u8> = tile_map
+                .ids
+                .iter()
+                .flat_map(|id| id.to_le_bytes())
+                .collect();
+            write_texture(queue, &targets.tile_ids, &ids, targets.width * 2, size);
+        }
+
+        if let Some(cover) = cover {
+            if cover.dimensions() != size {
+                return;
+            }
+
+            write_texture(queue, &targets.cover, &cover, targets.width * 4, size);
+        }
+
+        if let Some(covered) = covered {
+            let rows = (covered.len() as u32).div_ceil(COVERED_ROW).max(1);
+            let mut texels: Vec<u8> = covered.iter().map(|&c| u8::from(c) * 255).collect();
+            texels.resize((rows * COVERED_ROW) as usize, 0);
+            write_texture(
+                queue,
+                &targets.covered,
+                &texels,
+                COVERED_ROW,
+                (COVERED_ROW, rows),
+            );
+        }
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("compositor_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &targets.output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &targets.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+
+    fn create_targets(
+        &mut self,
+        device: &wgpu::Device,
+        egui_rpass: &mut RenderPass,
+        width: u32,
+        height: u32,
+    ) {
+        let texture = |label, (width, height), format, usage| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+            })
+        };
+        let input = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        let size = (width, height);
+
+        let original = texture(
+            "compositor_original",
+            size,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            input,
+        );
+        let tile_ids = texture(
+            "compositor_tile_ids",
+            size,
+            wgpu::TextureFormat::R16Uint,
+            input,
+        );
+        let max_rows = (MAX_TILES as u32 + 1) / COVERED_ROW;
+        let cover = texture(
+            "compositor_cover",
+            size,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            input,
+        );
+        let covered = texture(
+            "compositor_covered",
+            (COVERED_ROW, max_rows),
+            wgpu::TextureFormat::R8Unorm,
+            input,
+        );
+        let output = texture(
+            "compositor_output",
+            size,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        )
+        .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let views = [&original, &tile_ids, &covered, &cover]
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("compositor_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&views[0]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&views[1]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&views[2]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&views[3]),
+                },
+            ],
+        });
+
+        let filter = wgpu::FilterMode::Linear;
+        self.texture_id = match self.texture_id {
+            Some(id) => {
+                egui_rpass
+                    .update_egui_texture_from_wgpu_texture(device, &output, filter, id)
+                    .expect("Texture was registered before.");
+                Some(id)
+            }
+            None => Some(egui_rpass.egui_texture_from_wgpu_texture(device, &output, filter)),
+        };
+
+        self.targets = Some(Targets {
+            width,
+            height,
+            original,
+            tile_ids,
+            covered,
+            cover,
+            output,
+            bind_group,
+        });
+    }
+}
+
+fn write_texture(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    data: &[u8],
+    bytes_per_row: u32,
+    (width, height): (u32, u32),
+) {
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: NonZeroU32::new(bytes_per_row),
+            rows_per_image: None,
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
@@ -0,0 +1,44 @@
+// Composites the frame of the tile reveal: the original image with the covered tiles showing
+// the cover instead. Every pixel looks up its tile in `tile_ids` and the tile in `covered`.
+
+@group(0) @binding(0)
+var original: texture_2d<f32>;
+@group(0) @binding(1)
+var tile_ids: texture_2d<u32>;
+@group(0) @binding(2)
+var covered: texture_2d<f32>;
+@group(0) @binding(3)
+var cover: texture_2d<f32>;
+
+// Width of the rows `covered` is laid out in, one texel per tile.
+let COVERED_ROW: u32 = 256u;
+// Tile id of pixels that belong to no tile.
+let NO_TILE: u32 = 65535u;
+
+// A triangle covering the whole target.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn tile_at(pixel: vec2<i32>) -> u32 {
+    return textureLoad(tile_ids, pixel, 0).r;
+}
+
+fn is_covered(tile: u32) -> bool {
+    if (tile == NO_TILE) {
+        return false;
+    }
+    let texel = vec2<i32>(i32(tile % COVERED_ROW), i32(tile / COVERED_ROW));
+    return textureLoad(covered, texel, 0).r > 0.5;
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(position.xy);
+    if (is_covered(tile_at(pixel))) {
+        return textureLoad(cover, pixel, 0);
+    }
+    return textureLoad(original, pixel, 0);
+}
@@ -1,30 +1,96 @@
-use crate::display_window::DisplayWindow;
+use crate::compositor::Composition;
+use crate::display_window::{DisplayState, DisplayWindow};
+use crate::my_image::{
+    create_black_image, open_animation, open_image, Animation, CoverImage, CoverSettings,
+    CoverStyle, DynamicImageConvert, FrameConvert,
+};
+use crate::overlay::TileOverlay;
+use crate::pack::{save_pack, PackImage, QuizPack};
+use crate::playlist::{list_images, load_thumbnail, Playlist, PlaylistEntry};
+use crate::reveal::{
+    format_pixelate_steps, format_tile_order, parse_pixelate_steps, parse_tile_order, FrameChange,
+    HostView, RevealMode, RevealStrategy, RoundSettings, Strategy,
+};
+use crate::tiles::{find_tile, tile_span, RevealOrder, TileSettings, Tiling};
+use crate::transition::{TransitionStyle, Transitions};
 use crate::viewport::{Gui, GuiImpl};
 use crate::{EventLoopState, MyEvent};
-use crate::my_image::{create_black_image, DynamicImageConvert};
 
 use egui::epaint::image::ImageDelta;
 use egui::epaint::textures::TextureFilter;
 use egui::epaint::ColorImage;
-use egui::{Context, ImageButton, ImageData, Rect, TextureHandle, Vec2};
-use image::io::Reader as ImageReader;
-use image::DynamicImage;
-use rand::{thread_rng, Rng};
-use std::sync::mpsc;
+use egui::{
+    pos2, Align2, Color32, Context, FontId, ImageButton, ImageData, Key, Modifiers, Rect, Sense,
+    Stroke, TextureHandle, Vec2,
+};
+use image::{DynamicImage, RgbaImage};
+use rand::random;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use winit::window::WindowId;
 
+const MAX_GRID_SIZE: u8 = 64;
+const MAX_VORONOI_CELLS: u16 = 256;
+const MAX_BLUR_STEPS: u8 = 32;
+const MAX_ZOOM: f32 = 64.0;
+/// `DragValue` works in `f64`, seeds up to this are shown and typed back exactly.
+const MAX_SEED: u64 = u32::MAX as u64;
 
 enum ControlSignal {
-    OpenFile(u8, u8),
-    Reset(u8, u8),
+    /// Asks for an image and loads it, see `LoadFile`.
+    OpenFile(RoundSettings),
+    LoadFile(String, RoundSettings),
+    /// Asks for a folder and queues up every image in it.
+    AddFolderToPlaylist,
+    AddFilesToPlaylist,
+    /// Asks for a pack folder and replaces the playlist with its images.
+    OpenPack,
+    /// Asks for a folder and saves the images at the given paths there as a pack.
+    SavePack(Vec<(String, PackImage)>),
+    Reset(RoundSettings),
     RevealTile,
     RevealTileAt(u8, u8),
+    /// Reveals the tile the host typed, see `find_tile`.
+    RevealTileNamed(String),
+    RevealTileAtPoint(u32, u32),
+    DisplayResized(f32),
+    Undo,
+    Redo,
+    /// Asks for an image to cover the tiles with.
+    ChooseCoverImage,
+    /// Reveals everything that is left, one step per interval or all at once if it is zero.
+    RevealAll(Duration),
+    /// Starts revealing a step per interval, `None` pauses.
+    AutoReveal(Option<Duration>),
+    /// Sent by the worker to itself when the timer is due.
+    Tick,
+    /// Sent by the worker to itself when the next frame of an animated image is due.
+    NextFrame,
+    /// Follows a new tile order from the next reveal on, without starting the round over.
+    SetTileOrder(Vec<(u8, u8)>),
+    SaveTileOrder(Vec<(u8, u8)>),
     Exit,
 }
 
 enum Response {
-    NewImageLoaded(TextureHandle, TextureHandle),
+    /// Full and partial textures of the new image along with its path and saved tile order.
+    NewImageLoaded(TextureHandle, TextureHandle, String, Vec<(u8, u8)>),
+    /// Images to queue up.
+    PlaylistAdded(Thumbnails),
+    /// Images of a pack that replaces the playlist.
+    PackOpened(Thumbnails),
+    /// The timer stopped on its own, because everything is revealed or the round was reset.
+    TimerStopped,
+    /// Something the host asked for failed, the round is left as it was.
+    Error(String),
+    /// The image at the path could not be opened, along with why.
+    LoadFailed(String, String),
+    /// The cover image picked in the dialog.
+    CoverImageChosen(CoverImage),
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -33,6 +99,18 @@ enum Mode {
     Full,
 }
 
+/// Paths and thumbnails of images for the playlist, along with their rounds if they come from
+/// a pack.
+type Thumbnails = Vec<(String, TextureHandle, Option<PackImage>)>;
+
+/// A playlist image the worker is still loading.
+struct PendingLoad {
+    path: String,
+    /// Tiles and mode to go back to if the load fails.
+    tiles: TileSettings,
+    mode: RevealMode,
+}
+
 pub struct ControlPanel {
     control_tx: mpsc::Sender<ControlSignal>,
     response_rx: mpsc::Receiver<Response>,
@@ -41,40 +119,113 @@ pub struct ControlPanel {
     partial_texture: TextureHandle,
 
     mode: Mode,
-    rows: u8,
-    columns: u8,
+    settings: RoundSettings,
+    pixelate_steps_text: String,
+    display: DisplayState,
+    display_aspect: f32,
+    /// Clicking a tile in Full mode assigns it the next place in the order instead of
+    /// revealing it.
+    editing_order: bool,
+    /// The tile order was edited since it was last saved, it is saved once editing ends.
+    order_dirty: bool,
+    auto_reveal_secs: f32,
+    /// Interval of the animated reveal all, zero reveals everything at once.
+    reveal_all_secs: f32,
+    timer_running: bool,
+    /// Label of the tile to reveal, typed by the host.
+    tile_label: String,
+    playlist: Playlist,
+    /// Playlist image asked for but not loaded yet. The tiles and mode of the current entry
+    /// are not followed meanwhile, the settings already belong to the image being loaded.
+    pending_load: Option<PendingLoad>,
+    show_playlist: bool,
+    /// Why the last thing the host asked for failed, shown until the host dismisses it.
+    error: Option<String>,
 }
 
 impl ControlPanel {
-    pub fn new(ctx: Context) -> Self {
+    /// `composition` is where the render loop picks up frames to composite on the GPU.
+    pub fn new(ctx: Context, composition: Arc<Mutex<Composition>>, max_texture_size: u32) -> Self {
         let (control_tx, control_rx) = mpsc::channel();
         let (response_tx, response_rx) = mpsc::channel();
 
+        let settings = RoundSettings {
+            mode: RevealMode::Tiles,
+            seed: new_seed(),
+            tiles: TileSettings {
+                rows: 4,
+                columns: 4,
+                tiling: Tiling::Grid,
+                voronoi_cells: 32,
+            },
+            tile_order: vec![],
+            reveal_order: RevealOrder::Random,
+            tile_detail_bias: 0.0,
+            cover: CoverSettings {
+                style: CoverStyle::Solid,
+                color: [0, 0, 0],
+                image: None,
+                tile_image: false,
+            },
+            blur_steps: 8,
+            pixelate_steps: vec![4, 8, 16, 32, 64, 128],
+            zoom_start: 16.0,
+            zoom_factor: 2.0,
+            zoom_focus: None,
+            spotlight_radius: 0.1,
+            spotlight_softness: 0.3,
+        };
+        let pixelate_steps_text = format_pixelate_steps(&settings.pixelate_steps);
+        let display = DisplayState {
+            size: Arc::new(Mutex::new(Vec2::new(1920.0, 1080.0))),
+            transitions: Arc::new(Mutex::new(Transitions::new(TransitionStyle::None, 0.4))),
+            composition,
+            overlay: Arc::new(Mutex::new(TileOverlay::new(false))),
+            max_texture_size,
+        };
+
         let img = create_black_image(1920, 1080);
-        let initial_tile_data = gen_tiles(img.width(), img.height(), 4, 4);
         let initial_origial_image = image::DynamicImage::ImageRgba8(img);
-        let image_data = ImageData::from(DynamicImageConvert(initial_origial_image.clone()));
-        let initial_texture =
-            ctx.load_texture("initial_texture", image_data, TextureFilter::Linear);
+        let image_data = ImageData::from(DynamicImageConvert(initial_origial_image));
+        let initial_full_texture = ctx.load_texture(
+            "initial_full_texture",
+            image_data.clone(),
+            TextureFilter::Linear,
+        );
+        let initial_partial_texture =
+            ctx.load_texture("initial_partial_texture", image_data, TextureFilter::Linear);
 
         spawn_worker_thread(
             ctx,
             control_rx,
             response_tx,
-            initial_origial_image,
-            initial_texture.clone(),
-            initial_tile_data,
+            initial_full_texture.clone(),
+            initial_partial_texture.clone(),
+            settings.clone(),
+            display.clone(),
         );
 
         Self {
             control_tx,
             response_rx,
             child_window_id: None,
-            full_texture: initial_texture.clone(),
-            partial_texture: initial_texture,
+            full_texture: initial_full_texture,
+            partial_texture: initial_partial_texture,
             mode: Mode::Compact,
-            rows: 4,
-            columns: 4,
+            settings,
+            pixelate_steps_text,
+            display,
+            display_aspect: 1920.0 / 1080.0,
+            editing_order: false,
+            order_dirty: false,
+            auto_reveal_secs: 5.0,
+            reveal_all_secs: 0.0,
+            timer_running: false,
+            tile_label: String::new(),
+            playlist: Playlist::default(),
+            pending_load: None,
+            show_playlist: false,
+            error: None,
         }
     }
 
@@ -82,14 +233,102 @@ impl ControlPanel {
         self.child_window_id = None;
         self.mode = Mode::Compact;
     }
+
+    /// Queues up `images`, those from a pack with its settings and the rest with the current ones.
+    fn add_to_playlist(&mut self, images: Thumbnails) {
+        let entries = images.into_iter().map(|(path, thumbnail, image)| {
+            let image = image.unwrap_or_default();
+            let mut tiles = self.settings.tiles;
+            tiles.rows = image.rows.unwrap_or(tiles.rows).min(MAX_GRID_SIZE);
+            tiles.columns = image.columns.unwrap_or(tiles.columns).min(MAX_GRID_SIZE);
+            PlaylistEntry {
+                path,
+                thumbnail,
+                tiles,
+                mode: image.mode.unwrap_or(self.settings.mode),
+                question: image.question,
+            }
+        });
+        self.playlist.entries.extend(entries);
+        self.show_playlist = true;
+    }
+
+    /// Has the round follow the edited tile order, see `save_tile_order`.
+    fn tile_order_edited(&mut self) {
+        self.control_tx
+            .send(ControlSignal::SetTileOrder(
+                self.settings.tile_order.clone(),
+            ))
+            .expect("Receiver always lives");
+        self.order_dirty = true;
+    }
+
+    /// Saves the tile order next to the current image if it was edited. Has to happen before
+    /// another image is loaded, the order belongs to this one.
+    fn save_tile_order(&mut self) {
+        if self.order_dirty {
+            self.control_tx
+                .send(ControlSignal::SaveTileOrder(
+                    self.settings.tile_order.clone(),
+                ))
+                .expect("Receiver always lives");
+            self.order_dirty = false;
+        }
+    }
+
+    /// Loads the playlist entry at `idx` with the tiles it was last played with.
+    fn load_playlist_entry(&mut self, idx: Option<usize>) {
+        let Some(idx) = idx.filter(|&idx| idx < self.playlist.entries.len()) else {
+            return;
+        };
+        self.save_tile_order();
+        let entry = &self.playlist.entries[idx];
+
+        // With several loads pending, the image still shown is the one before the first.
+        let (tiles, mode) = match self.pending_load.take() {
+            Some(pending) => (pending.tiles, pending.mode),
+            None => (self.settings.tiles, self.settings.mode),
+        };
+        self.pending_load = Some(PendingLoad {
+            path: entry.path.clone(),
+            tiles,
+            mode,
+        });
+        self.settings.tiles = entry.tiles;
+        self.settings.mode = entry.mode;
+        self.control_tx
+            .send(ControlSignal::LoadFile(
+                entry.path.clone(),
+                self.settings.clone(),
+            ))
+            .expect("Receiver always lives");
+    }
 }
 
 impl Gui for ControlPanel {
     fn draw(&mut self, ctx: &egui::Context, state: EventLoopState) {
         if let Ok(res) = self.response_rx.try_recv() {
             match res {
-                Response::NewImageLoaded(full_texture, partial_texture) => {
+                Response::NewImageLoaded(full_texture, partial_texture, path, tile_order) => {
+                    self.playlist.current = self
+                        .playlist
+                        .entries
+                        .iter()
+                        .position(|entry| entry.path == path);
+                    match &mut self.pending_load {
+                        Some(pending) if pending.path == path => self.pending_load = None,
+                        // An earlier load finished while a later one is still pending, failing
+                        // now goes back to this image.
+                        Some(pending) => {
+                            if let Some(entry) = self.playlist.current_mut() {
+                                pending.tiles = entry.tiles;
+                                pending.mode = entry.mode;
+                            }
+                        }
+                        None => {}
+                    }
                     self.full_texture = full_texture;
+                    self.settings.tile_order = tile_order;
                     self.partial_texture = partial_texture.clone();
 
                     if let Some(id) = self.child_window_id {
@@ -98,11 +337,63 @@ impl Gui for ControlPanel {
                             .send_event(MyEvent::UpdateChildWindowData(id, partial_texture));
                     }
                 }
+                Response::PlaylistAdded(images) => self.add_to_playlist(images),
+                Response::PackOpened(images) => {
+                    self.playlist.clear();
+                    self.add_to_playlist(images);
+                }
+                Response::TimerStopped => self.timer_running = false,
+                Response::Error(reason) => self.error = Some(reason),
+                Response::LoadFailed(path, reason) => {
+                    // The settings go back to the image that is still shown.
+                    if matches!(&self.pending_load, Some(pending) if pending.path == path) {
+                        let pending = self.pending_load.take().expect("Matched above.");
+                        self.settings.tiles = pending.tiles;
+                        self.settings.mode = pending.mode;
+                    }
+                    self.error = Some(reason);
+                }
+                Response::CoverImageChosen(image) => {
+                    self.settings.cover.image = Some(image);
+                    self.control_tx
+                        .send(ControlSignal::Reset(self.settings.clone()))
+                        .expect("Receiver always lives.");
+                }
             }
         }
 
         // ctx.set_debug_on_hover(true);
 
+        if let Some(reason) = &self.error {
+            let mut dismissed = false;
+            egui::Window::new("Something went wrong")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(reason);
+                    dismissed = ui.button("OK").clicked();
+                });
+            if dismissed {
+                self.error = None;
+            }
+        }
+
+        // While a text field has focus the shortcuts undo typing instead.
+        if !ctx.wants_keyboard_input() {
+            if ctx.input_mut().consume_key(Modifiers::COMMAND, Key::Z) {
+                self.control_tx
+                    .send(ControlSignal::Undo)
+                    .expect("Receiver always lives");
+            }
+            if ctx.input_mut().consume_key(Modifiers::COMMAND, Key::Y) {
+                self.control_tx
+                    .send(ControlSignal::Redo)
+                    .expect("Receiver always lives");
+            }
+        }
+        let focus_tile_label = ctx.input_mut().consume_key(Modifiers::COMMAND, Key::L);
+
         let mut s = (*ctx.style()).clone();
         s.spacing.button_padding = [10.0, 10.0].into();
         ctx.set_style(s);
@@ -113,39 +404,222 @@ impl Gui for ControlPanel {
                 ui.heading("Image Guesser!");
 
                 if ui.button("Open file").clicked() {
+                    self.save_tile_order();
                     self.control_tx
-                        .send(ControlSignal::OpenFile(self.rows, self.columns))
+                        .send(ControlSignal::OpenFile(self.settings.clone()))
                         .expect("Receiver always lives");
                 }
+                if !self.playlist.is_empty() {
+                    let previous = self.playlist.previous();
+                    if ui
+                        .add_enabled(previous.is_some(), egui::Button::new("Previous image"))
+                        .clicked()
+                    {
+                        self.load_playlist_entry(previous);
+                    }
+                    let next = self.playlist.next();
+                    if ui
+                        .add_enabled(next.is_some(), egui::Button::new("Next image"))
+                        .clicked()
+                    {
+                        self.load_playlist_entry(next);
+                    }
+                }
+                ui.toggle_value(&mut self.show_playlist, "Playlist");
                 if ui.button("Reset").clicked() {
                     self.control_tx
-                        .send(ControlSignal::Reset(self.rows, self.columns))
+                        .send(ControlSignal::Reset(self.settings.clone()))
                         .expect("Receiver always lives");
                 }
 
-                let old_rows = self.rows;
-                egui::ComboBox::from_label("Number of rows")
-                    .selected_text(format!("{:?}", self.rows))
+                let mut old_settings = self.settings.clone();
+                egui::ComboBox::from_label("Reveal mode")
+                    .selected_text(self.settings.mode.name())
                     .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut self.rows, 2, "2");
-                        ui.selectable_value(&mut self.rows, 4, "4");
-                        ui.selectable_value(&mut self.rows, 6, "6");
-                        ui.selectable_value(&mut self.rows, 8, "8");
+                        for mode in RevealMode::ALL {
+                            ui.selectable_value(&mut self.settings.mode, mode, mode.name());
+                        }
                     });
 
-                let old_columns = self.columns;
-                egui::ComboBox::from_label("Number of columns")
-                    .selected_text(format!("{:?}", self.columns))
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut self.columns, 2, "2");
-                        ui.selectable_value(&mut self.columns, 4, "4");
-                        ui.selectable_value(&mut self.columns, 6, "6");
-                        ui.selectable_value(&mut self.columns, 8, "8");
-                    });
+                match self.settings.mode {
+                    RevealMode::Tiles => {
+                        if self.settings.tiles.tiling == Tiling::Voronoi {
+                            ui.label("Number of cells");
+                            ui.add(
+                                egui::DragValue::new(&mut self.settings.tiles.voronoi_cells)
+                                    .clamp_range(2..=MAX_VORONOI_CELLS),
+                            );
+                        } else {
+                            ui.label("Number of rows");
+                            ui.add(
+                                egui::DragValue::new(&mut self.settings.tiles.rows)
+                                    .clamp_range(1..=MAX_GRID_SIZE),
+                            );
+
+                            ui.label("Number of columns");
+                            ui.add(
+                                egui::DragValue::new(&mut self.settings.tiles.columns)
+                                    .clamp_range(1..=MAX_GRID_SIZE),
+                            );
+                        }
+
+                        egui::ComboBox::from_label("Tiling")
+                            .selected_text(self.settings.tiles.tiling.name())
+                            .show_ui(ui, |ui| {
+                                for tiling in Tiling::ALL {
+                                    ui.selectable_value(
+                                        &mut self.settings.tiles.tiling,
+                                        tiling,
+                                        tiling.name(),
+                                    );
+                                }
+                            });
+
+                        egui::ComboBox::from_label("Order")
+                            .selected_text(self.settings.reveal_order.name())
+                            .show_ui(ui, |ui| {
+                                for order in RevealOrder::ALL {
+                                    ui.selectable_value(
+                                        &mut self.settings.reveal_order,
+                                        order,
+                                        order.name(),
+                                    );
+                                }
+                            });
+
+                        if self.settings.reveal_order == RevealOrder::Random {
+                            ui.label("Hide details");
+                            ui.add(
+                                egui::DragValue::new(&mut self.settings.tile_detail_bias)
+                                    .clamp_range(0.0..=1.0)
+                                    .speed(0.01),
+                            );
+                        }
+
+                        if self.mode == Mode::Full {
+                            ui.checkbox(&mut self.editing_order, "Edit order");
+                        }
+                        if !self.settings.tile_order.is_empty()
+                            && ui.button("Clear order").clicked()
+                        {
+                            self.settings.tile_order.clear();
+                        }
+                    }
+                    RevealMode::Blur => {
+                        ui.label("Number of steps");
+                        ui.add(
+                            egui::DragValue::new(&mut self.settings.blur_steps)
+                                .clamp_range(1..=MAX_BLUR_STEPS),
+                        );
+                    }
+                    RevealMode::Pixelate => {
+                        ui.label("Blocks per step");
+                        let response = ui.text_edit_singleline(&mut self.pixelate_steps_text);
+                        if response.lost_focus() {
+                            match parse_pixelate_steps(&self.pixelate_steps_text) {
+                                Some(steps) => self.settings.pixelate_steps = steps,
+                                None => {
+                                    self.pixelate_steps_text =
+                                        format_pixelate_steps(&self.settings.pixelate_steps)
+                                }
+                            }
+                        }
+                    }
+                    RevealMode::Zoom => {
+                        ui.label("Start zoom");
+                        ui.add(
+                            egui::DragValue::new(&mut self.settings.zoom_start)
+                                .clamp_range(1.0..=MAX_ZOOM)
+                                .speed(0.1),
+                        );
+
+                        ui.label("Zoom out by");
+                        ui.add(
+                            egui::DragValue::new(&mut self.settings.zoom_factor)
+                                .clamp_range(1.1..=4.0)
+                                .speed(0.05),
+                        );
+
+                        if self.settings.zoom_focus.is_some() && ui.button("Random focus").clicked()
+                        {
+                            self.settings.zoom_focus = None;
+                        }
+                    }
+                    RevealMode::Spotlight => {
+                        ui.label("Radius");
+                        ui.add(
+                            egui::DragValue::new(&mut self.settings.spotlight_radius)
+                                .clamp_range(0.01..=1.0)
+                                .speed(0.005),
+                        );
 
-                if old_rows != self.rows || old_columns != self.columns {
+                        ui.label("Softness");
+                        ui.add(
+                            egui::DragValue::new(&mut self.settings.spotlight_softness)
+                                .clamp_range(0.0..=1.0)
+                                .speed(0.01),
+                        );
+                    }
+                    RevealMode::Tone => {}
+                }
+
+                if matches!(
+                    self.settings.mode,
+                    RevealMode::Tiles | RevealMode::Spotlight
+                ) {
+                    // Only tiles have numbers, without them the cover is solid anyway.
+                    let has_tiles = self.settings.mode == RevealMode::Tiles;
+                    if !has_tiles && self.settings.cover.style == CoverStyle::Numbers {
+                        self.settings.cover.style = CoverStyle::Solid;
+                    }
+                    egui::ComboBox::from_label("Cover")
+                        .selected_text(self.settings.cover.style.name())
+                        .show_ui(ui, |ui| {
+                            let styles = CoverStyle::ALL.into_iter();
+                            for style in styles.filter(|&s| has_tiles || s != CoverStyle::Numbers) {
+                                ui.selectable_value(
+                                    &mut self.settings.cover.style,
+                                    style,
+                                    style.name(),
+                                );
+                            }
+                        });
+                    match self.settings.cover.style {
+                        CoverStyle::Solid | CoverStyle::Numbers => {
+                            ui.color_edit_button_srgb(&mut self.settings.cover.color);
+                        }
+                        CoverStyle::Image => {
+                            if ui.button("Choose cover image").clicked() {
+                                self.control_tx
+                                    .send(ControlSignal::ChooseCoverImage)
+                                    .expect("Receiver always lives.");
+                            }
+                            ui.checkbox(&mut self.settings.cover.tile_image, "Tile");
+                        }
+                        CoverStyle::Noise | CoverStyle::Blurred => {}
+                    }
+                }
+
+                // Replaying a seed replays the round, so shows can be rehearsed.
+                if matches!(
+                    self.settings.mode,
+                    RevealMode::Tiles | RevealMode::Zoom | RevealMode::Spotlight
+                ) {
+                    ui.label("Seed");
+                    ui.add(egui::DragValue::new(&mut self.settings.seed).clamp_range(0..=MAX_SEED));
+                    if ui.button("New seed").clicked() {
+                        self.settings.seed = new_seed();
+                    }
+                }
+
+                // A new tile order only changes which tiles come next.
+                if old_settings.tile_order != self.settings.tile_order {
+                    self.tile_order_edited();
+                    old_settings.tile_order = self.settings.tile_order.clone();
+                }
+                if old_settings != self.settings {
                     self.control_tx
-                        .send(ControlSignal::Reset(self.rows, self.columns))
+                        .send(ControlSignal::Reset(self.settings.clone()))
                         .expect("Receiver always lives.");
                 }
 
@@ -154,48 +628,372 @@ impl Gui for ControlPanel {
                         .send(ControlSignal::RevealTile)
                         .expect("Receiver always lives");
                 }
+                // The host types the tile the audience asked for, e.g. B3 or the number on it,
+                // and presses enter.
+                let label_edit = ui.add(
+                    egui::TextEdit::singleline(&mut self.tile_label)
+                        .hint_text("Tile")
+                        .desired_width(40.0),
+                );
+                if focus_tile_label {
+                    label_edit.request_focus();
+                }
+                if label_edit.lost_focus() && ui.input().key_pressed(Key::Enter) {
+                    if !self.tile_label.trim().is_empty() {
+                        self.control_tx
+                            .send(ControlSignal::RevealTileNamed(self.tile_label.clone()))
+                            .expect("Receiver always lives");
+                    }
+                    self.tile_label.clear();
+                    label_edit.request_focus();
+                }
+                if ui.button("Undo").clicked() {
+                    self.control_tx
+                        .send(ControlSignal::Undo)
+                        .expect("Receiver always lives");
+                }
+                if ui.button("Redo").clicked() {
+                    self.control_tx
+                        .send(ControlSignal::Redo)
+                        .expect("Receiver always lives");
+                }
+
+                if self.timer_running {
+                    if ui.button("Pause").clicked() {
+                        self.timer_running = false;
+                        self.control_tx
+                            .send(ControlSignal::AutoReveal(None))
+                            .expect("Receiver always lives");
+                    }
+                } else if ui.button("Auto reveal").clicked() {
+                    self.timer_running = true;
+                    let interval = Duration::from_secs_f32(self.auto_reveal_secs);
+                    self.control_tx
+                        .send(ControlSignal::AutoReveal(Some(interval)))
+                        .expect("Receiver always lives");
+                }
+                ui.label("every");
+                ui.add(
+                    egui::DragValue::new(&mut self.auto_reveal_secs)
+                        .clamp_range(0.1..=60.0)
+                        .speed(0.1)
+                        .suffix(" s"),
+                );
+
+                if ui.button("Reveal all").clicked() {
+                    self.timer_running = self.reveal_all_secs > 0.0;
+                    let interval = Duration::from_secs_f32(self.reveal_all_secs);
+                    self.control_tx
+                        .send(ControlSignal::RevealAll(interval))
+                        .expect("Receiver always lives");
+                }
+                ui.label("one by one every");
+                ui.add(
+                    egui::DragValue::new(&mut self.reveal_all_secs)
+                        .clamp_range(0.0..=10.0)
+                        .speed(0.05)
+                        .suffix(" s"),
+                );
+
+                let mut transitions = self.display.transitions.lock().unwrap();
+                egui::ComboBox::from_label("Transition")
+                    .selected_text(transitions.style.name())
+                    .show_ui(ui, |ui| {
+                        for style in TransitionStyle::ALL {
+                            ui.selectable_value(&mut transitions.style, style, style.name());
+                        }
+                    });
+                if transitions.style != TransitionStyle::None {
+                    ui.add(
+                        egui::DragValue::new(&mut transitions.duration)
+                            .clamp_range(0.05..=2.0)
+                            .speed(0.01)
+                            .suffix(" s"),
+                    );
+                }
+                drop(transitions);
+
+                ui.checkbox(
+                    &mut self.display.overlay.lock().unwrap().enabled,
+                    "Tile labels",
+                );
 
                 ui.radio_value(&mut self.mode, Mode::Compact, "Compact");
                 ui.radio_value(&mut self.mode, Mode::Full, "Full");
             });
         });
 
+        let (tiles, mode) = (self.settings.tiles, self.settings.mode);
+        if let Some(entry) = self
+            .playlist
+            .current_mut()
+            .filter(|_| self.pending_load.is_none())
+        {
+            entry.tiles = tiles;
+            entry.mode = mode;
+        }
+
+        if self.show_playlist {
+            egui::SidePanel::left("Playlist").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Add folder").clicked() {
+                        self.control_tx
+                            .send(ControlSignal::AddFolderToPlaylist)
+                            .expect("Receiver always lives");
+                    }
+                    if ui.button("Add files").clicked() {
+                        self.control_tx
+                            .send(ControlSignal::AddFilesToPlaylist)
+                            .expect("Receiver always lives");
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.playlist.clear();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Open pack").clicked() {
+                        self.control_tx
+                            .send(ControlSignal::OpenPack)
+                            .expect("Receiver always lives");
+                    }
+                    if ui
+                        .add_enabled(!self.playlist.is_empty(), egui::Button::new("Save pack"))
+                        .clicked()
+                    {
+                        let images = self
+                            .playlist
+                            .entries
+                            .iter()
+                            .map(|entry| (entry.path.clone(), entry.pack_image()))
+                            .collect();
+                        self.control_tx
+                            .send(ControlSignal::SavePack(images))
+                            .expect("Receiver always lives");
+                    }
+                });
+
+                // Only the host sees the answer, the display window never shows it.
+                if let Some(entry) = self.playlist.current_mut() {
+                    ui.separator();
+                    let question = &mut entry.question;
+                    egui::Grid::new("question").num_columns(2).show(ui, |ui| {
+                        ui.label("Answer");
+                        ui.text_edit_singleline(&mut question.answer);
+                        ui.end_row();
+                        ui.label("Also accepted");
+                        edit_list(ui, &mut question.alternates);
+                        ui.end_row();
+                        ui.label("Category");
+                        ui.text_edit_singleline(&mut question.category);
+                        ui.end_row();
+                        ui.label("Points");
+                        ui.add(egui::DragValue::new(&mut question.points));
+                        ui.end_row();
+                        ui.label("Hints");
+                        edit_list(ui, &mut question.hints);
+                        ui.end_row();
+                    });
+                }
+                ui.separator();
+
+                let mut load = None;
+                let mut move_up = None;
+                let mut remove = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (idx, entry) in self.playlist.entries.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let size = entry.thumbnail.size_vec2();
+                            let thumbnail = ImageButton::new(entry.thumbnail.id(), size)
+                                .selected(self.playlist.current == Some(idx));
+                            if ui.add(thumbnail).clicked() {
+                                load = Some(idx);
+                            }
+                            ui.vertical(|ui| {
+                                ui.label(format!("{}. {}", idx + 1, entry.name()));
+                                let question = &entry.question;
+                                if !question.category.is_empty() {
+                                    ui.weak(&question.category);
+                                }
+                                if question.points > 0 {
+                                    ui.weak(format!("{} points", question.points));
+                                }
+                                ui.horizontal(|ui| {
+                                    if ui.add_enabled(idx > 0, egui::Button::new("Up")).clicked() {
+                                        move_up = Some(idx);
+                                    }
+                                    let last = idx + 1 == self.playlist.entries.len();
+                                    if ui.add_enabled(!last, egui::Button::new("Down")).clicked() {
+                                        move_up = Some(idx + 1);
+                                    }
+                                    if ui.button("Remove").clicked() {
+                                        remove = Some(idx);
+                                    }
+                                });
+                            });
+                        });
+                    }
+                });
+
+                if let Some(idx) = move_up {
+                    self.playlist.move_up(idx);
+                }
+                if let Some(idx) = remove {
+                    self.playlist.remove(idx);
+                }
+                self.load_playlist_entry(load);
+            });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             if self.mode == Mode::Compact {
-                ui.image(self.partial_texture.id(), ui.available_size());
-            } else {
+                self.display.show(ui, &self.partial_texture);
+            } else if self.settings.mode == RevealMode::Zoom {
+                // The host picks the point to zoom into by clicking the full image.
+                let image = egui::Image::new(self.full_texture.id(), ui.available_size())
+                    .sense(Sense::click());
+                let response = ui.add(image);
+                if let Some(pos) = response
+                    .interact_pointer_pos()
+                    .filter(|_| response.clicked())
+                {
+                    let uv = (pos - response.rect.min) / response.rect.size();
+                    self.settings.zoom_focus = Some((uv.x, uv.y));
+                    self.control_tx
+                        .send(ControlSignal::Reset(self.settings.clone()))
+                        .expect("Receiver always lives");
+                }
+
+                if let Some((x, y)) = self.settings.zoom_focus {
+                    let center = response.rect.min + response.rect.size() * Vec2::new(x, y);
+                    let stroke = Stroke::new(2.0, Color32::RED);
+                    ui.painter().circle_stroke(center, 10.0, stroke);
+                }
+            } else if self.settings.mode == RevealMode::Tiles
+                && self.settings.tiles.tiling == Tiling::Grid
+            {
+                let TileSettings { rows, columns, .. } = self.settings.tiles;
                 let space = ui.available_size();
-                let space_x = space.x / self.columns as f32;
-                let space_y = space.y / self.rows as f32;
-                let size: Vec2 = [space_x - 30.0, space_y - 30.0].into();
+                let [width, height] = self.full_texture.size();
+                let (width, height) = (width as u32, height as u32);
 
                 egui::Grid::new("buttons").show(ui, |ui| {
-                    for x in 0..self.rows {
+                    for x in 0..rows {
+                        let (pos_y, tile_height) = tile_span(height, rows, x);
                         let (start_y, end_y) = (
-                            (space_y * x as f32) / space.y,
-                            (space_y * (x + 1) as f32) / space.y,
+                            pos_y as f32 / height as f32,
+                            (pos_y + tile_height) as f32 / height as f32,
                         );
-                        for y in 0..self.columns {
+                        for y in 0..columns {
+                            let (pos_x, tile_width) = tile_span(width, columns, y);
                             let (start_x, end_x) = (
-                                (space_x * y as f32) / space.x,
-                                (space_x * (y + 1) as f32) / space.x,
+                                pos_x as f32 / width as f32,
+                                (pos_x + tile_width) as f32 / width as f32,
                             );
 
+                            let size: Vec2 = [
+                                (space.x * (end_x - start_x) - 30.0).max(1.0),
+                                (space.y * (end_y - start_y) - 30.0).max(1.0),
+                            ]
+                            .into();
                             let rect = Rect::from_x_y_ranges(start_x..=end_x, start_y..=end_y);
                             let b = ImageButton::new(self.full_texture.id(), size).uv(rect);
 
-                            if ui.add(b).clicked() {
+                            let response = ui.add(b);
+                            let place = self.settings.tile_order.iter().position(|&t| t == (x, y));
+
+                            if response.clicked() && self.editing_order {
+                                // Clicking an ordered tile again takes it out of the order.
+                                toggle_tile_order(&mut self.settings.tile_order, (x, y));
+                                self.tile_order_edited();
+                            } else if response.clicked() {
                                 self.control_tx
                                     .send(ControlSignal::RevealTileAt(x, y))
                                     .expect("Receiver always lives");
                             }
+
+                            if let Some(place) = place {
+                                ui.painter().text(
+                                    response.rect.center(),
+                                    Align2::CENTER_CENTER,
+                                    place + 1,
+                                    FontId::proportional(32.0),
+                                    Color32::YELLOW,
+                                );
+                            }
                         }
                         ui.end_row();
                     }
                 });
+            } else if matches!(
+                self.settings.mode,
+                RevealMode::Tiles | RevealMode::Spotlight
+            ) {
+                // Shaped tiles and spotlights do not fit a grid of buttons, the whole image is
+                // one click target with the tile outlines baked into the full texture.
+                let image = egui::Image::new(self.full_texture.id(), ui.available_size())
+                    .sense(Sense::click());
+                let response = ui.add(image);
+                let editing_order = self.editing_order && self.settings.mode == RevealMode::Tiles;
+                if let Some(pos) = response
+                    .interact_pointer_pos()
+                    .filter(|_| response.clicked())
+                {
+                    let uv = (pos - response.rect.min) / response.rect.size();
+                    if editing_order {
+                        let tile = self.display.overlay.lock().unwrap().tile_at(uv.to_pos2());
+                        if let Some(tile) = tile {
+                            toggle_tile_order(&mut self.settings.tile_order, tile);
+                            self.tile_order_edited();
+                        }
+                    } else {
+                        let [width, height] = self.full_texture.size();
+                        let x = ((uv.x * width as f32) as u32).min(width as u32 - 1);
+                        let y = ((uv.y * height as f32) as u32).min(height as u32 - 1);
+                        self.control_tx
+                            .send(ControlSignal::RevealTileAtPoint(x, y))
+                            .expect("Receiver always lives");
+                    }
+                }
+
+                if self.settings.mode == RevealMode::Tiles {
+                    let overlay = self.display.overlay.lock().unwrap();
+                    for (place, &(row, column)) in self.settings.tile_order.iter().enumerate() {
+                        if let Some(uv) = overlay.tile_center(row, column) {
+                            ui.painter().text(
+                                response.rect.lerp(uv.to_vec2()),
+                                Align2::CENTER_CENTER,
+                                place + 1,
+                                FontId::proportional(32.0),
+                                Color32::YELLOW,
+                            );
+                        }
+                    }
+                }
+            } else {
+                ui.image(self.full_texture.id(), ui.available_size());
             }
         });
 
+        // The order is saved once the host is done editing it.
+        if !(self.editing_order
+            && self.mode == Mode::Full
+            && self.settings.mode == RevealMode::Tiles)
+        {
+            self.save_tile_order();
+        }
+
+        let display_size = *self.display.size.lock().unwrap();
+        let display_aspect = display_size.x / display_size.y;
+        if display_aspect.is_finite()
+            && display_aspect > 0.0
+            && (display_aspect - self.display_aspect).abs() > 0.01
+        {
+            self.display_aspect = display_aspect;
+            self.control_tx
+                .send(ControlSignal::DisplayResized(display_aspect))
+                .expect("Receiver always lives");
+        }
+
         match self.child_window_id {
             Some(id) if self.mode == Mode::Compact => {
                 // We are currently in Full Mode and wish to switch to Compact
@@ -204,13 +1002,13 @@ impl Gui for ControlPanel {
             }
             None if self.mode == Mode::Full => {
                 // We are currently in Compact Mode and wish to switch to Full
-                let gui = DisplayWindow::new(self.partial_texture.clone());
+                let gui = DisplayWindow::new(self.partial_texture.clone(), self.display.clone());
                 let (new_window_id, new_vp) =
                     state.create_window("Image Guesser!", 1920, 1080, GuiImpl::DisplayWindow(gui));
                 self.child_window_id = Some(new_window_id);
                 let _ = state
                     .event_loop_proxy
-                    .send_event(MyEvent::OpenWindow(new_window_id, new_vp));
+                    .send_event(MyEvent::OpenWindow(new_window_id, Box::new(new_vp)));
             }
             _ => {}
         };
@@ -219,6 +1017,7 @@ impl Gui for ControlPanel {
 
 impl Drop for ControlPanel {
     fn drop(&mut self) {
+        self.save_tile_order();
         let _ = self.control_tx.send(ControlSignal::Exit);
     }
 }
@@ -227,156 +1026,551 @@ fn spawn_worker_thread(
     ctx: Context,
     control_rx: mpsc::Receiver<ControlSignal>,
     response_tx: mpsc::Sender<Response>,
-    initial_original_image: DynamicImage,
+    initial_full_texture: TextureHandle,
     initial_texture: TextureHandle,
-    intial_tile_data: TileData,
+    initial_settings: RoundSettings,
+    display: DisplayState,
 ) {
+    // The initial textures show a black placeholder until a file is opened.
+    let [width, height] = initial_full_texture.size();
+    let mut original_image =
+        DynamicImage::ImageRgba8(create_black_image(width as u32, height as u32));
+    let mut full_texture = initial_full_texture;
     let mut texture = initial_texture;
-    let mut tile_data = intial_tile_data;
-    let mut original_image = initial_original_image;
+    let mut display_aspect = 1920.0 / 1080.0;
+    let mut image_path = None;
+    let mut timer: Option<Timer> = None;
+    let mut playback: Option<Playback> = None;
+    let mut strategy = Strategy::new(&initial_settings, display_aspect);
+    strategy.reset(&original_image);
+    // The placeholder is shown to the host as it is.
+    let mut host_view = HostView::Original;
+    // A signal taken off the queue early while skipping resets, handled next.
+    let mut pending: Option<ControlSignal> = None;
+    display
+        .composition
+        .lock()
+        .unwrap()
+        .set_image(original_image.to_rgba8());
+    composite(&display, &strategy);
 
     thread::spawn(move || loop {
-        match control_rx.recv().expect("Sender always lives.") {
-            ControlSignal::OpenFile(rows, columns) => {
-                if let Some(s) = tinyfiledialogs::open_file_dialog("Choose screenshot ;)", "", None)
-                {
-                    let i = ImageReader::open(s).unwrap().decode().unwrap();
-                    let td = gen_tiles(i.width(), i.height(), rows, columns);
-                    let original_image_data = ImageData::from(DynamicImageConvert(i.clone()));
-                    let full_texture = ctx.load_texture(
-                        "full_texture",
-                        original_image_data,
-                        TextureFilter::Linear,
-                    );
-
-                    let black_image =
-                        image::DynamicImage::ImageRgba8(create_black_image(i.width(), i.height()));
-                    let black_image_data = ImageData::from(DynamicImageConvert(black_image));
-                    let partial_texture = ctx.load_texture(
-                        "partial_texture",
-                        black_image_data,
-                        TextureFilter::Linear,
-                    );
-
-                    original_image = i;
-                    texture = partial_texture;
-                    tile_data = td;
-
-                    response_tx
-                        .send(Response::NewImageLoaded(full_texture, texture.clone()))
-                        .expect("Receiver always lives.");
-                };
+        let next_frame = playback.as_ref().map(|playback| playback.next);
+        let deadline = [timer.as_ref().map(|timer| timer.next), next_frame]
+            .into_iter()
+            .flatten()
+            .min();
+        let signal = match (pending.take(), deadline) {
+            (Some(signal), _) => signal,
+            (None, Some(deadline)) => {
+                match control_rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(signal) => signal,
+                    Err(RecvTimeoutError::Timeout) if next_frame == Some(deadline) => {
+                        ControlSignal::NextFrame
+                    }
+                    Err(RecvTimeoutError::Timeout) => ControlSignal::Tick,
+                    Err(RecvTimeoutError::Disconnected) => panic!("Sender always lives."),
+                }
             }
-            ControlSignal::RevealTile => {
-                let num_of_tiles = tile_data.tiles.len();
-                if num_of_tiles == 0 {
-                    continue;
+            (None, None) => control_rx.recv().expect("Sender always lives."),
+        };
+        // Dragging the number of tiles queues a reset per step, and large tilings take a while
+        // to cut. Only the last of the resets waiting in a row is worth doing.
+        let signal = match signal {
+            ControlSignal::Reset(mut settings) => {
+                while let Ok(next) = control_rx.try_recv() {
+                    match next {
+                        ControlSignal::Reset(next_settings) => settings = next_settings,
+                        next => {
+                            pending = Some(next);
+                            break;
+                        }
+                    }
                 }
+                ControlSignal::Reset(settings)
+            }
+            signal => signal,
+        };
+        // Opening a file is loading whichever file gets picked.
+        let signal = match signal {
+            ControlSignal::OpenFile(settings) => {
+                match tinyfiledialogs::open_file_dialog("Choose screenshot ;)", "", None) {
+                    Some(path) => ControlSignal::LoadFile(path, settings),
+                    None => continue,
+                }
+            }
+            signal => signal,
+        };
 
-                let tile = tile_data
-                    .tiles
-                    .remove(thread_rng().gen_range(0..=num_of_tiles - 1));
+        let change = match signal {
+            ControlSignal::OpenFile(_) => unreachable!("Opening a file turns into loading it."),
+            ControlSignal::LoadFile(s, mut settings) => {
+                // A file that cannot be opened or shown leaves the round as it was.
+                let max = display.max_texture_size;
+                let opened = match open_animation(&s) {
+                    Ok((image, _)) if image.width() > max || image.height() > max => Err(format!(
+                        "{s} is {}x{} pixels, the graphics card can only show up to {max}x{max}.",
+                        image.width(),
+                        image.height()
+                    )),
+                    Ok(opened) => Ok(opened),
+                    Err(err) => Err(format!("Could not open {s}: {err}")),
+                };
+                let (i, animation) = match opened {
+                    Ok(opened) => opened,
+                    Err(reason) => {
+                        response_tx
+                            .send(Response::LoadFailed(s, reason))
+                            .expect("Receiver always lives.");
+                        continue;
+                    }
+                };
+                stop_timer(&mut timer, &response_tx);
+                display.transitions.lock().unwrap().clear();
+                settings.tile_order = fs::read_to_string(tile_order_path(&s))
+                    .ok()
+                    .and_then(|text| parse_tile_order(&text))
+                    .unwrap_or_default();
+                let mut new_strategy = Strategy::new(&settings, display_aspect);
+                new_strategy.reset(&i);
 
-                let original = &original_image;
-                let original_tile = original.crop_imm(
-                    tile.x as _,
-                    tile.y as _,
-                    tile_data.tile_width,
-                    tile_data.tile_height,
-                );
+                let original_image_data =
+                    ImageData::from(DynamicImageConvert(new_strategy.host_image(&i)));
+                let new_full_texture =
+                    ctx.load_texture("full_texture", original_image_data, TextureFilter::Linear);
 
-                let position = [tile.x as _, tile.y as _];
-                let delta = ImageDelta::partial(
-                    position,
-                    ColorImage::from(DynamicImageConvert(original_tile)),
-                    TextureFilter::Linear,
-                );
+                display.composition.lock().unwrap().set_image(i.to_rgba8());
+                composite(&display, &new_strategy);
+                // Composited frames never read the partial texture, the old one can stay.
+                if !display.composition.lock().unwrap().is_active() {
+                    let frame_data = ImageData::from(FrameConvert(new_strategy.frame()));
+                    texture =
+                        ctx.load_texture("partial_texture", frame_data, TextureFilter::Linear);
+                }
+                original_image = i;
+                host_view = new_strategy.host_view();
+                strategy = new_strategy;
+                full_texture = new_full_texture;
+                image_path = Some(s.clone());
+                playback = Playback::new(animation);
 
-                let tex_mgr = ctx.tex_manager();
-                tex_mgr.write().set(texture.id(), delta);
+                response_tx
+                    .send(Response::NewImageLoaded(
+                        full_texture.clone(),
+                        texture.clone(),
+                        s,
+                        settings.tile_order,
+                    ))
+                    .expect("Receiver always lives.");
+                None
             }
-            ControlSignal::RevealTileAt(x, y) => {
-                let idx = tile_data.tiles.iter().position(|tile| {
-                    tile.x as u32 == y as u32 * tile_data.tile_width
-                        && tile.y as u32 == x as u32 * tile_data.tile_height
-                });
-
-                if let Some(idx) = idx {
-                    let tile = tile_data.tiles.remove(idx);
-                    let original = &original_image;
-                    let original_tile = original.crop_imm(
-                        tile.x as _,
-                        tile.y as _,
-                        tile_data.tile_width,
-                        tile_data.tile_height,
-                    );
+            ControlSignal::AddFolderToPlaylist => {
+                if let Some(folder) = tinyfiledialogs::select_folder_dialog("Choose a folder", "") {
+                    let images = list_images(&folder)
+                        .into_iter()
+                        .map(|path| (path, None))
+                        .collect();
+                    spawn_thumbnails(&ctx, &response_tx, images, Response::PlaylistAdded);
+                }
+                None
+            }
+            ControlSignal::AddFilesToPlaylist => {
+                if let Some(paths) =
+                    tinyfiledialogs::open_file_dialog_multi("Choose screenshots", "", None)
+                {
+                    let images = paths.into_iter().map(|path| (path, None)).collect();
+                    spawn_thumbnails(&ctx, &response_tx, images, Response::PlaylistAdded);
+                }
+                None
+            }
+            ControlSignal::OpenPack => {
+                if let Some(folder) = tinyfiledialogs::select_folder_dialog("Choose a pack", "") {
+                    match QuizPack::load(&folder) {
+                        Ok(pack) => {
+                            let images = pack
+                                .images
+                                .into_iter()
+                                .map(|image| {
+                                    let path = Path::new(&folder).join(&image.file);
+                                    (path.to_string_lossy().into_owned(), Some(image))
+                                })
+                                .collect();
+                            spawn_thumbnails(&ctx, &response_tx, images, Response::PackOpened);
+                        }
+                        Err(err) => response_tx
+                            .send(Response::Error(format!("Could not open the pack: {err}")))
+                            .expect("Receiver always lives."),
+                    }
+                }
+                None
+            }
+            ControlSignal::SavePack(images) => {
+                if let Some(folder) =
+                    tinyfiledialogs::select_folder_dialog("Choose a folder for the pack", "")
+                {
+                    if let Err(err) = save_pack(&folder, images) {
+                        response_tx
+                            .send(Response::Error(format!(
+                                "Could not save the pack to {folder}: {err}"
+                            )))
+                            .expect("Receiver always lives.");
+                    }
+                }
+                None
+            }
+            ControlSignal::RevealTile if strategy.is_complete() => None,
+            ControlSignal::RevealTile => strategy.step(&original_image),
+            ControlSignal::RevealTileAt(x, y) => strategy.reveal_tile_at(&original_image, x, y),
+            ControlSignal::RevealTileNamed(name) => {
+                match strategy.tiles().and_then(|tiles| find_tile(&name, tiles)) {
+                    Some((row, column)) => strategy.reveal_tile_at(&original_image, row, column),
+                    None => None,
+                }
+            }
+            ControlSignal::RevealTileAtPoint(x, y) => strategy.reveal_at(&original_image, x, y),
+            ControlSignal::Reset(settings) => {
+                stop_timer(&mut timer, &response_tx);
+                display.transitions.lock().unwrap().clear();
+                strategy = Strategy::new(&settings, display_aspect);
+                strategy.reset(&original_image);
+                composite(&display, &strategy);
 
-                    let position = [tile.x as _, tile.y as _];
-                    let delta = ImageDelta::partial(
-                        position,
-                        ColorImage::from(DynamicImageConvert(original_tile)),
-                        TextureFilter::Linear,
-                    );
+                // Most settings, like the seed or the cover, leave the host view as it is.
+                if strategy.host_view() != host_view {
+                    host_view = strategy.host_view();
+                    let full_image_data =
+                        ImageData::from(DynamicImageConvert(strategy.host_image(&original_image)));
+                    let full_delta = ImageDelta::full(full_image_data, TextureFilter::Linear);
 
                     let tex_mgr = ctx.tex_manager();
-                    tex_mgr.write().set(texture.id(), delta);
+                    tex_mgr.write().set(full_texture.id(), full_delta);
                 }
+                Some(FrameChange::Full)
             }
-            ControlSignal::Reset(rows, columns) => {
-                tile_data = gen_tiles(
-                    original_image.width(),
-                    original_image.height(),
-                    rows,
-                    columns,
-                );
-                let black = image::DynamicImage::ImageRgba8(create_black_image(
-                    original_image.width(),
-                    original_image.height(),
-                ));
-                let image_data = ImageData::from(DynamicImageConvert(black));
-                let delta = ImageDelta::full(image_data, TextureFilter::Linear);
-
-                tile_data = tile_data;
-                let tex_mgr = ctx.tex_manager();
-                tex_mgr.write().set(texture.id(), delta);
+            ControlSignal::Undo => strategy.undo(&original_image),
+            ControlSignal::Redo => strategy.redo(&original_image),
+            ControlSignal::RevealAll(interval) if interval.is_zero() => {
+                stop_timer(&mut timer, &response_tx);
+                strategy.reveal_all(&original_image)
+            }
+            ControlSignal::RevealAll(interval) | ControlSignal::AutoReveal(Some(interval)) => {
+                timer = Some(Timer::new(interval));
+                None
+            }
+            ControlSignal::ChooseCoverImage => {
+                if let Some(path) =
+                    tinyfiledialogs::open_file_dialog("Choose cover image", "", None)
+                {
+                    // Decoded once here, every reset of the round reuses it.
+                    let response = match open_image(&path) {
+                        Ok(image) => {
+                            Response::CoverImageChosen(CoverImage(Arc::new(image.to_rgba8())))
+                        }
+                        Err(err) => Response::Error(format!("Could not open {path}: {err}")),
+                    };
+                    response_tx.send(response).expect("Receiver always lives.");
+                }
+                None
+            }
+            ControlSignal::AutoReveal(None) => {
+                timer = None;
+                None
+            }
+            ControlSignal::NextFrame => {
+                let playing = playback.as_mut().expect("The next frame is due.");
+                match playing.next_frame() {
+                    Some(frame) => {
+                        let mut composition = display.composition.lock().unwrap();
+                        if composition.is_active() {
+                            composition.set_image(frame.clone());
+                        }
+                        drop(composition);
+                        original_image = DynamicImage::ImageRgba8(frame);
+                        strategy.original_changed(&original_image)
+                    }
+                    // The last frame that could be decoded stays.
+                    None => {
+                        playback = None;
+                        None
+                    }
+                }
+            }
+            ControlSignal::Tick => {
+                let change = strategy.step(&original_image);
+                if strategy.is_complete() {
+                    stop_timer(&mut timer, &response_tx);
+                } else if let Some(timer) = &mut timer {
+                    timer.next += timer.interval;
+                }
+                change
+            }
+            ControlSignal::DisplayResized(aspect) => {
+                display_aspect = aspect;
+                strategy.display_resized(&original_image, aspect)
+            }
+            ControlSignal::SetTileOrder(order) => {
+                strategy.set_tile_order(order);
+                None
+            }
+            ControlSignal::SaveTileOrder(order) => {
+                if let Some(path) = &image_path {
+                    let path = tile_order_path(path);
+                    let result = if order.is_empty() {
+                        fs::remove_file(&path).or_else(|err| match err.kind() {
+                            std::io::ErrorKind::NotFound => Ok(()),
+                            _ => Err(err),
+                        })
+                    } else {
+                        fs::write(&path, format_tile_order(&order))
+                    };
+                    if let Err(err) = result {
+                        response_tx
+                            .send(Response::Error(format!(
+                                "Could not save the tile order to {path}: {err}"
+                            )))
+                            .expect("Receiver always lives.");
+                    }
+                }
+                None
             }
             ControlSignal::Exit => return,
+        };
+
+        // Only changed regions are animated, the strategy knows what they showed before.
+        let replaced = match change {
+            Some(FrameChange::Region { .. })
+                if display.transitions.lock().unwrap().is_enabled() =>
+            {
+                strategy.replaced(&original_image)
+            }
+            _ => None,
+        };
+        if let (
+            Some(snapshot),
+            Some(FrameChange::Region {
+                x,
+                y,
+                width,
+                height,
+            }),
+        ) = (replaced, change)
+        {
+            let snapshot_texture = ctx.load_texture(
+                "transition_texture",
+                ImageData::from(FrameConvert(&snapshot)),
+                TextureFilter::Linear,
+            );
+            let (frame_width, frame_height) = strategy.frame().dimensions();
+            let uv = Rect::from_min_max(
+                pos2(
+                    x as f32 / frame_width as f32,
+                    y as f32 / frame_height as f32,
+                ),
+                pos2(
+                    (x + width) as f32 / frame_width as f32,
+                    (y + height) as f32 / frame_height as f32,
+                ),
+            );
+            display
+                .transitions
+                .lock()
+                .unwrap()
+                .add(snapshot_texture, uv);
+        }
+
+        if let Some(change) = change {
+            if let Some(covered) = strategy.covered_tiles() {
+                display.overlay.lock().unwrap().set_covered(covered);
+            }
+            let mut composition = display.composition.lock().unwrap();
+            match strategy.covered_tiles() {
+                Some(covered) if composition.is_active() => composition.set_covered(covered),
+                _ => show_change(&ctx, &texture, strategy.frame(), change),
+            }
         }
     });
 }
 
-#[derive(Copy, Clone, Debug)]
-struct Tile {
-    x: i64,
-    y: i64,
+/// Has the frame composited on the GPU if the strategy allows it and labels its tiles.
+fn composite(display: &DisplayState, strategy: &Strategy) {
+    let mut composition = display.composition.lock().unwrap();
+    match (
+        strategy.tile_map(),
+        strategy.cover(),
+        strategy.covered_tiles(),
+    ) {
+        (Some(tile_map), Some(cover), Some(covered)) => {
+            composition.show_tiles(tile_map, cover, covered)
+        }
+        _ => composition.hide(),
+    }
+
+    let mut overlay = display.overlay.lock().unwrap();
+    match (strategy.tiles(), strategy.covered_tiles()) {
+        (Some(tiles), Some(covered)) => {
+            let (width, height) = strategy.frame().dimensions();
+            overlay.set_tiles(tiles, width, height);
+            overlay.set_covered(covered);
+        }
+        _ => overlay.clear(),
+    }
 }
 
-#[derive(Clone, Debug)]
-struct TileData {
-    tiles: Vec<Tile>,
-    tile_width: u32,
-    tile_height: u32,
+/// Makes thumbnails of the images at the given paths for the playlist on a thread of their own,
+/// so the round goes on while a big folder is decoded. They are sent back wrapped in `respond`,
+/// the images that cannot be opened are left out and reported.
+fn spawn_thumbnails(
+    ctx: &Context,
+    response_tx: &mpsc::Sender<Response>,
+    images: Vec<(String, Option<PackImage>)>,
+    respond: fn(Thumbnails) -> Response,
+) {
+    let (ctx, response_tx) = (ctx.clone(), response_tx.clone());
+    thread::spawn(move || {
+        let thumbnails = load_thumbnails(&ctx, &response_tx, images);
+        response_tx
+            .send(respond(thumbnails))
+            .expect("Receiver always lives.");
+    });
 }
 
-fn gen_tiles(width: u32, height: u32, rows: u8, columns: u8) -> TileData {
-    let tile_height = height / rows as u32;
-    let tile_width = width / columns as u32;
-
-    let mut tiles = vec![];
-    for x in 0..rows {
-        let pos_y = x as u32 * tile_height;
-        for y in 0..columns {
-            let pos_x = y as u32 * tile_width;
-            tiles.push(Tile {
-                x: pos_x as _,
-                y: pos_y as _,
-            });
+fn load_thumbnails(
+    ctx: &Context,
+    response_tx: &mpsc::Sender<Response>,
+    images: Vec<(String, Option<PackImage>)>,
+) -> Thumbnails {
+    let mut failed = vec![];
+    let images = images
+        .into_iter()
+        .filter_map(|(path, image)| match load_thumbnail(&path) {
+            Ok(thumbnail) => {
+                let texture = ctx.load_texture(
+                    "thumbnail_texture",
+                    ImageData::from(DynamicImageConvert(thumbnail)),
+                    TextureFilter::Linear,
+                );
+                Some((path, texture, image))
+            }
+            Err(err) => {
+                failed.push(format!("{path}: {err}"));
+                None
+            }
+        })
+        .collect();
+
+    if !failed.is_empty() {
+        response_tx
+            .send(Response::Error(format!(
+                "Could not open these images, they were left out:\n{}",
+                failed.join("\n")
+            )))
+            .expect("Receiver always lives.");
+    }
+    images
+}
+
+/// Edits `items` one per line, so they can hold any other character. Blank lines are kept
+/// while editing so a new line can be started, see `Question::trimmed`.
+fn edit_list(ui: &mut egui::Ui, items: &mut Vec<String>) {
+    let mut text = items.join("\n");
+    if ui.text_edit_multiline(&mut text).changed() {
+        *items = if text.is_empty() {
+            vec![]
+        } else {
+            text.split('\n').map(String::from).collect()
+        };
+    }
+}
+
+struct Timer {
+    interval: Duration,
+    next: Instant,
+}
+
+impl Timer {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Instant::now() + interval,
         }
     }
+}
+
+/// Plays the frames of an animated image, every reveal mode shows them.
+struct Playback {
+    animation: Animation,
+    /// When the frame after the current one is due.
+    next: Instant,
+}
 
-    TileData {
-        tiles,
-        tile_width,
-        tile_height,
+impl Playback {
+    /// `None` for still images, there is nothing to play.
+    fn new(animation: Option<Animation>) -> Option<Self> {
+        let animation = animation?;
+        Some(Self {
+            next: Instant::now() + animation.first_delay(),
+            animation,
+        })
     }
+
+    /// Moves on to the next frame, `None` when the animation cannot go on.
+    fn next_frame(&mut self) -> Option<RgbaImage> {
+        let (frame, delay) = self.animation.next_frame()?;
+        // Frames the worker was too busy to show are skipped rather than played back in a rush.
+        self.next = (self.next + delay).max(Instant::now() + delay / 2);
+        Some(frame)
+    }
+}
+
+/// Stops the timer, telling the control panel if it was running.
+fn stop_timer(timer: &mut Option<Timer>, response_tx: &mpsc::Sender<Response>) {
+    if timer.take().is_some() {
+        response_tx
+            .send(Response::TimerStopped)
+            .expect("Receiver always lives.");
+    }
+}
+
+fn new_seed() -> u64 {
+    random::<u32>() as u64
+}
+
+/// Puts `tile` last in `order`, or takes it out if it is in there already.
+fn toggle_tile_order(order: &mut Vec<(u8, u8)>, tile: (u8, u8)) {
+    match order.iter().position(|&t| t == tile) {
+        Some(place) => {
+            order.remove(place);
+        }
+        None => order.push(tile),
+    }
+}
+
+/// The tile order of an image is saved next to it, so it comes back when the image is opened.
+fn tile_order_path(image_path: &str) -> String {
+    format!("{image_path}.order")
+}
+
+/// Uploads the changed part of `frame` to the texture.
+fn show_change(ctx: &Context, texture: &TextureHandle, frame: &RgbaImage, change: FrameChange) {
+    let delta = match change {
+        FrameChange::Full => {
+            ImageDelta::full(ImageData::from(FrameConvert(frame)), TextureFilter::Linear)
+        }
+        FrameChange::Region {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let region = image::imageops::crop_imm(frame, x, y, width, height).to_image();
+            ImageDelta::partial(
+                [x as _, y as _],
+                ColorImage::from(FrameConvert(&region)),
+                TextureFilter::Linear,
+            )
+        }
+    };
+
+    let tex_mgr = ctx.tex_manager();
+    tex_mgr.write().set(texture.id(), delta);
 }
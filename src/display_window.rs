@@ -1,20 +1,65 @@
-use egui::{style::Margin, Frame, TextureHandle};
+use std::sync::{Arc, Mutex};
 
-use crate::{viewport::Gui, EventLoopState};
+use egui::{style::Margin, Frame, TextureHandle, Ui, Vec2};
+
+use crate::compositor::Composition;
+use crate::overlay::TileOverlay;
+use crate::{transition::Transitions, viewport::Gui, EventLoopState};
+
+/// State shared by the worker and the windows showing the audience frame.
+#[derive(Clone)]
+pub struct DisplayState {
+    /// Area the frame is drawn in.
+    pub size: Arc<Mutex<Vec2>>,
+    pub transitions: Arc<Mutex<Transitions>>,
+    pub composition: Arc<Mutex<Composition>>,
+    pub overlay: Arc<Mutex<TileOverlay>>,
+    /// Largest width and height of a texture the device can hold, larger images cannot be shown.
+    pub max_texture_size: u32,
+}
+
+impl DisplayState {
+    /// Draws the composited frame, or `texture` when there is none, with the transitions and the
+    /// tile overlay over it.
+    pub fn show(&self, ui: &mut Ui, texture: &TextureHandle) {
+        *self.size.lock().unwrap() = ui.available_size();
+        let texture_id = self
+            .composition
+            .lock()
+            .unwrap()
+            .texture_id()
+            .unwrap_or_else(|| texture.id());
+        let response = ui.image(texture_id, ui.available_size());
+        let time = ui.input().time;
+        self.transitions
+            .lock()
+            .unwrap()
+            .paint(ui.painter(), response.rect, time);
+        self.overlay
+            .lock()
+            .unwrap()
+            .paint(ui.painter(), response.rect);
+    }
+}
 
 pub struct DisplayWindow {
     texture: TextureHandle,
     frame: Frame,
+    display: DisplayState,
 }
 
 impl DisplayWindow {
-    pub fn new(texture: TextureHandle) -> Self {
+    pub fn new(texture: TextureHandle, display: DisplayState) -> Self {
         let frame = Frame {
             inner_margin: Margin::same(0.0),
             ..Default::default()
         };
 
-        Self { texture, frame }
+        Self {
+            texture,
+            frame,
+            display,
+        }
     }
 }
 
@@ -28,8 +73,6 @@ impl Gui for DisplayWindow {
     fn draw(&mut self, ctx: &egui::Context, _state: EventLoopState) {
         egui::CentralPanel::default()
             .frame(self.frame)
-            .show(ctx, |ui| {
-                ui.image(self.texture.id(), ui.available_size());
-            });
+            .show(ctx, |ui| self.display.show(ui, &self.texture));
     }
 }
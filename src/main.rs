@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::iter;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
+use compositor::{Composition, Compositor};
 use egui::TextureHandle;
 use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
 use viewport::{Gui, GuiImpl, Viewport, ViewportDesc};
@@ -10,16 +12,23 @@ use winit::event::{ElementState, Event::*, KeyboardInput, VirtualKeyCode};
 use winit::event_loop::{EventLoopProxy, EventLoopWindowTarget};
 use winit::window::{Fullscreen, Window, WindowId};
 
+mod compositor;
 mod control_panel;
 mod display_window;
 mod my_image;
+mod overlay;
+mod pack;
+mod playlist;
+mod reveal;
+mod tiles;
+mod transition;
 mod viewport;
 
 const INITIAL_WIDTH: u32 = 1920;
 const INITIAL_HEIGHT: u32 = 1080;
 
 pub enum MyEvent {
-    OpenWindow(WindowId, Viewport),
+    OpenWindow(WindowId, Box<Viewport>),
     CloseWindow(WindowId),
     UpdateChildWindowData(WindowId, TextureHandle),
 }
@@ -43,14 +52,20 @@ fn main() {
         &wgpu::DeviceDescriptor {
             label: None,
             features: wgpu::Features::empty(),
-            limits: wgpu::Limits::downlevel_defaults(),
+            // Screenshots can be larger than the downlevel texture size limit.
+            limits: wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
         },
         None,
     ))
     .expect("Failed to create device");
 
+    let composition = Arc::new(Mutex::new(Composition::default()));
     let vp = main_vp_desc.build(&adapter, &device, |ctx| {
-        let main_gui = control_panel::ControlPanel::new(ctx.clone());
+        let main_gui = control_panel::ControlPanel::new(
+            ctx.clone(),
+            composition.clone(),
+            device.limits().max_texture_dimension_2d,
+        );
         GuiImpl::ControlPanel(main_gui)
     });
 
@@ -59,6 +74,7 @@ fn main() {
 
     let surface_config = viewports.iter().next().unwrap().1.config.format;
     let mut egui_rpass = RenderPass::new(&device, surface_config, 1);
+    let mut compositor = Compositor::new(&device);
 
     let start_time = Instant::now();
 
@@ -106,6 +122,8 @@ fn main() {
                     physical_height: vp.config.height,
                     scale_factor: vp.window.scale_factor() as f32,
                 };
+                compositor.prepare(&device, &queue, &mut encoder, &mut egui_rpass, &composition);
+
                 let tdelta: egui::TexturesDelta = full_output.textures_delta;
                 egui_rpass
                     .add_textures(&device, &queue, &tdelta)
@@ -131,7 +149,7 @@ fn main() {
             }
             UserEvent(e) => match e {
                 MyEvent::OpenWindow(window_id, viewport) => {
-                    viewports.insert(window_id, viewport);
+                    viewports.insert(window_id, *viewport);
                 }
                 MyEvent::CloseWindow(window_id) => {
                     viewports.remove(&window_id);
@@ -157,10 +175,9 @@ fn main() {
                 event: ref window_event,
                 window_id,
             } => {
-                match viewports.get_mut(&window_id) {
-                    Some(vp) => vp.platform.handle_event(&event),
-                    None => {}
-                };
+                if let Some(vp) = viewports.get_mut(&window_id) {
+                    vp.platform.handle_event(&event);
+                }
 
                 match window_event {
                     winit::event::WindowEvent::Resized(size) => {
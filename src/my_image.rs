@@ -1,16 +1,34 @@
 use egui::ColorImage;
 use egui::ImageData;
-use image::{DynamicImage, RgbaImage};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use image::codecs::gif::GifDecoder;
+use image::codecs::jpeg::JpegDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::tiff::TiffDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::error::{DecodingError, ImageFormatHint};
+use image::imageops::{self, FilterType};
+use image::io::Reader as ImageReader;
+use image::{
+    AnimationDecoder, DynamicImage, Frame, Frames, GenericImageView, ImageDecoder, ImageError,
+    ImageFormat, ImageResult, Rgba, RgbaImage,
+};
+use qcms::{DataType, Intent, Profile, Transform};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 pub struct DynamicImageConvert(pub DynamicImage);
 
 impl From<DynamicImageConvert> for ColorImage {
     fn from(img: DynamicImageConvert) -> Self {
-        let img = img.0;
-        let size = [img.width() as _, img.height() as _];
-        let image_buffer = img.to_rgba8();
-        let pixels = image_buffer.as_flat_samples();
-        egui::ColorImage::from_rgba_unmultiplied(size, pixels.as_slice())
+        ColorImage::from(FrameConvert(&img.0.to_rgba8()))
     }
 }
 
@@ -20,8 +38,799 @@ impl From<DynamicImageConvert> for ImageData {
     }
 }
 
+/// Converts a frame that is already RGBA, such as a reveal frame or a frame of an animation,
+/// without copying it into a `DynamicImage` first.
+pub struct FrameConvert<'a>(pub &'a RgbaImage);
+
+impl From<FrameConvert<'_>> for ColorImage {
+    fn from(frame: FrameConvert) -> Self {
+        let size = [frame.0.width() as _, frame.0.height() as _];
+        egui::ColorImage::from_rgba_unmultiplied(size, frame.0.as_raw())
+    }
+}
+
+impl From<FrameConvert<'_>> for ImageData {
+    fn from(frame: FrameConvert) -> Self {
+        ImageData::Color(ColorImage::from(frame))
+    }
+}
+
 pub fn create_black_image(width: u32, height: u32) -> RgbaImage {
     let mut empty_image = RgbaImage::new(width, height);
     empty_image.pixels_mut().for_each(|p| p.0 = [0, 0, 0, 255]);
     empty_image
 }
+
+/// Decodes the image at `path`, upright and in sRGB colours. The format is told by the
+/// contents when the extension is missing or wrong, except for SVGs which need theirs.
+///
+/// AVIF is only opened with the `avif` feature, decoding it needs the dav1d C library.
+pub fn open_image(path: &str) -> ImageResult<DynamicImage> {
+    if is_svg(Path::new(path)) {
+        return open_svg(path);
+    }
+
+    let reader = ImageReader::open(path)?.with_guessed_format()?;
+    match reader.format() {
+        Some(ImageFormat::Jpeg) => decode(path, JpegDecoder::new(reader.into_inner())?),
+        Some(ImageFormat::Png) => decode(path, PngDecoder::new(reader.into_inner())?),
+        Some(ImageFormat::WebP) => decode(path, WebPDecoder::new(reader.into_inner())?),
+        Some(ImageFormat::Tiff) => decode(path, TiffDecoder::new(reader.into_inner())?),
+        _ => reader.decode(),
+    }
+}
+
+/// Decodes an image the way its metadata says it should be shown, the colour profile is read
+/// from the decoder that decodes the pixels.
+fn decode<'a>(path: &str, mut decoder: impl ImageDecoder<'a>) -> ImageResult<DynamicImage> {
+    let correction = Correction::read(path, &mut decoder);
+    Ok(correction.apply(DynamicImage::from_decoder(decoder)?))
+}
+
+/// Longer side SVGs are rendered at, their own size is usually that of an icon.
+const SVG_SIZE: u32 = 1920;
+
+/// Whether the file at `path` is an SVG, by its extension.
+pub fn is_svg(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("svg") || extension.eq_ignore_ascii_case("svgz")
+        })
+}
+
+/// Renders the SVG at `path` so its longer side is `SVG_SIZE` pixels.
+fn open_svg(path: &str) -> ImageResult<DynamicImage> {
+    let error = |err: &dyn std::fmt::Display| {
+        ImageError::Decoding(DecodingError::new(
+            ImageFormatHint::Name("SVG".to_string()),
+            err.to_string(),
+        ))
+    };
+
+    let data = fs::read(path)?;
+    let tree = usvg::Tree::from_data(&data, &usvg::Options::default().to_ref())
+        .map_err(|err| error(&err))?;
+    let fit_to = usvg::FitTo::Size(SVG_SIZE, SVG_SIZE);
+    let size = fit_to
+        .fit_to(tree.svg_node().size.to_screen_size())
+        .ok_or_else(|| error(&"the image has no size"))?;
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| error(&"the image has no size"))?;
+    resvg::render(
+        &tree,
+        fit_to,
+        tiny_skia::Transform::default(),
+        pixmap.as_mut(),
+    )
+    .ok_or_else(|| error(&"the image could not be rendered"))?;
+
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    let image = RgbaImage::from_raw(size.width(), size.height(), pixels)
+        .expect("The pixmap has a pixel for every pixel of the image.");
+    Ok(DynamicImage::ImageRgba8(image))
+}
+
+/// What has to be done to a decoded image to show it the way it was taken, read from its
+/// metadata: phones store photos sideways with an EXIF orientation, and wide gamut images
+/// look washed out unless their colour profile is converted to sRGB.
+#[derive(Default)]
+struct Correction {
+    orientation: Option<u32>,
+    /// Converts from the colour profile of the image to sRGB.
+    transform: Option<Transform>,
+}
+
+impl Correction {
+    fn read<'a>(path: &str, decoder: &mut impl ImageDecoder<'a>) -> Self {
+        Self {
+            orientation: read_orientation(path),
+            transform: decoder
+                .icc_profile()
+                .and_then(|profile| srgb_transform(&profile)),
+        }
+    }
+
+    fn apply(&self, image: DynamicImage) -> DynamicImage {
+        let image = match self.orientation {
+            Some(orientation) => apply_orientation(image, orientation),
+            None => image,
+        };
+        match &self.transform {
+            Some(transform) => {
+                let mut image = image.into_rgba8();
+                transform.apply(&mut image);
+                DynamicImage::ImageRgba8(image)
+            }
+            None => image,
+        }
+    }
+}
+
+/// The EXIF orientation of the image at `path`, from 1, upright, to 8, see
+/// `apply_orientation`.
+fn read_orientation(path: &str) -> Option<u32> {
+    let mut file = BufReader::new(File::open(path).ok()?);
+    let exif = exif::Reader::new().read_from_container(&mut file).ok()?;
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+        .value
+        .get_uint(0)
+        .filter(|orientation| (1..=8).contains(orientation))
+}
+
+/// Turns an image stored with the given EXIF orientation upright.
+fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// Converts RGBA pixels from the ICC profile `profile` to sRGB, `None` if it cannot be read.
+/// Only RGB profiles are converted: qcms would read the RGBA pixels as grey and alpha for a
+/// grey profile, and does not expect CMYK ones at all.
+fn srgb_transform(profile: &[u8]) -> Option<Transform> {
+    if profile.get(16..20) != Some(b"RGB ") {
+        return None;
+    }
+    let profile = Profile::new_from_slice(profile, false)?;
+    Transform::new(
+        &profile,
+        &Profile::new_sRGB(),
+        DataType::RGBA8,
+        Intent::default(),
+    )
+}
+
+/// Frames that are shown for less than this are shown for `DEFAULT_FRAME_DELAY` instead, the
+/// same as browsers do, as such delays are almost always meant as "no delay set".
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+/// Animations whose frames take up more than this are decoded again on every loop instead of
+/// being kept in memory.
+const MAX_CACHED_BYTES: usize = 256 << 20;
+/// Frames decoded ahead of the one shown.
+const FRAMES_AHEAD: usize = 2;
+
+/// A decoded frame and how long it shows.
+pub type AnimationFrame = (RgbaImage, Duration);
+
+/// What the decoder thread sends, `None` after the last frame of every loop.
+type DecodedFrame = ImageResult<Option<AnimationFrame>>;
+
+/// The frames of an animated image after the first, decoded on a thread of their own as they
+/// are needed. Short animations are kept in memory after the first loop, longer ones are
+/// decoded again on every loop.
+pub struct Animation {
+    /// The second frame, taken from the decoder to tell the image is animated.
+    second: Option<AnimationFrame>,
+    /// `None` once every frame is cached or decoding failed.
+    frames_rx: Option<Receiver<DecodedFrame>>,
+    /// Every frame of the first loop so far, cleared when they get too big to keep.
+    cached: Vec<AnimationFrame>,
+    cached_bytes: usize,
+    caching: bool,
+    /// Cached frame to show next once the first loop is over.
+    next_cached: usize,
+    first_delay: Duration,
+}
+
+impl Animation {
+    /// How long the first frame shows.
+    pub fn first_delay(&self) -> Duration {
+        self.first_delay
+    }
+
+    /// The frame after the last one returned, starting over after the last frame. `None` when
+    /// a frame cannot be decoded, the animation stops there.
+    pub fn next_frame(&mut self) -> Option<AnimationFrame> {
+        if let Some(second) = self.second.take() {
+            return Some(second);
+        }
+        if let Some(frames_rx) = &self.frames_rx {
+            loop {
+                match frames_rx.recv() {
+                    Ok(Ok(Some(frame))) => {
+                        self.cache(&frame);
+                        return Some(frame);
+                    }
+                    // A loop is over, the cache holds all of it unless it got too big.
+                    Ok(Ok(None)) if self.caching => {
+                        self.frames_rx = None;
+                        break;
+                    }
+                    Ok(Ok(None)) => continue,
+                    Ok(Err(_)) | Err(_) => {
+                        self.frames_rx = None;
+                        self.cached.clear();
+                        return None;
+                    }
+                }
+            }
+        }
+
+        let frame = self.cached.get(self.next_cached)?.clone();
+        self.next_cached = (self.next_cached + 1) % self.cached.len();
+        Some(frame)
+    }
+
+    fn cache(&mut self, frame: &AnimationFrame) {
+        if !self.caching {
+            return;
+        }
+        self.cached_bytes += frame.0.as_raw().len();
+        if self.cached_bytes > MAX_CACHED_BYTES {
+            self.caching = false;
+            self.cached = vec![];
+        } else {
+            self.cached.push(frame.clone());
+        }
+    }
+}
+
+/// Decodes the image at `path` like `open_image`, along with the rest of its frames if it is
+/// an animated GIF, PNG or WebP.
+pub fn open_animation(path: &str) -> ImageResult<(DynamicImage, Option<Animation>)> {
+    let reader = ImageReader::open(path)?.with_guessed_format()?;
+    let format = reader.format();
+    // The decoder that tells still images apart decodes them too.
+    let correction = match format {
+        // GIFs have neither an orientation nor a colour profile.
+        Some(ImageFormat::Gif) => Correction::default(),
+        Some(ImageFormat::Png) => {
+            let mut decoder = PngDecoder::new(reader.into_inner())?;
+            if !decoder.is_apng() {
+                return Ok((decode(path, decoder)?, None));
+            }
+            Correction::read(path, &mut decoder)
+        }
+        Some(ImageFormat::WebP) => {
+            let mut decoder = WebPDecoder::new(reader.into_inner())?;
+            if !decoder.has_animation() {
+                return Ok((decode(path, decoder)?, None));
+            }
+            Correction::read(path, &mut decoder)
+        }
+        _ => return Ok((open_image(path)?, None)),
+    };
+    let format = format.expect("Only known formats are animated.");
+
+    let (frames_tx, frames_rx) = mpsc::sync_channel(FRAMES_AHEAD);
+    let owned_path = path.to_string();
+    thread::spawn(move || decode_frames(&owned_path, format, &correction, &frames_tx));
+
+    let (first, first_delay) = match frames_rx.recv() {
+        Ok(Ok(Some(first))) => first,
+        Ok(Err(err)) => return Err(err),
+        _ => return Ok((open_image(path)?, None)),
+    };
+    // Animations of a single frame are still images.
+    let second = match frames_rx.recv() {
+        Ok(Ok(Some(second))) => second,
+        _ => return Ok((DynamicImage::ImageRgba8(first), None)),
+    };
+
+    let mut animation = Animation {
+        second: None,
+        frames_rx: Some(frames_rx),
+        cached: vec![],
+        cached_bytes: 0,
+        caching: true,
+        next_cached: 0,
+        first_delay,
+    };
+    animation.cache(&(first.clone(), first_delay));
+    animation.cache(&second);
+    animation.second = Some(second);
+    Ok((DynamicImage::ImageRgba8(first), Some(animation)))
+}
+
+/// The frames of the animated image at `path`.
+fn animation_frames(path: &str, format: ImageFormat) -> ImageResult<Frames<'static>> {
+    let file = BufReader::new(File::open(path)?);
+    Ok(match format {
+        ImageFormat::Gif => GifDecoder::new(file)?.into_frames(),
+        ImageFormat::Png => PngDecoder::new(file)?.apng().into_frames(),
+        ImageFormat::WebP => WebPDecoder::new(file)?.into_frames(),
+        _ => unreachable!("Only GIF, PNG and WebP are animated."),
+    })
+}
+
+/// Sends the frames of the animation at `path` over and over, until the receiver is gone or
+/// a frame cannot be decoded.
+fn decode_frames(
+    path: &str,
+    format: ImageFormat,
+    correction: &Correction,
+    frames_tx: &SyncSender<DecodedFrame>,
+) {
+    loop {
+        let frames = match animation_frames(path, format) {
+            Ok(frames) => frames,
+            Err(err) => {
+                let _ = frames_tx.send(Err(err));
+                return;
+            }
+        };
+        for frame in frames {
+            let frame = frame.map(|frame| {
+                let delay = frame_delay(&frame);
+                let frame = correction.apply(DynamicImage::ImageRgba8(frame.into_buffer()));
+                Some((frame.into_rgba8(), delay))
+            });
+            let failed = frame.is_err();
+            if frames_tx.send(frame).is_err() || failed {
+                return;
+            }
+        }
+        if frames_tx.send(Ok(None)).is_err() {
+            return;
+        }
+    }
+}
+
+fn frame_delay(frame: &Frame) -> Duration {
+    let (numerator, denominator) = frame.delay().numer_denom_ms();
+    let delay = Duration::from_secs_f64(numerator as f64 / denominator.max(1) as f64 / 1000.0);
+    if delay < MIN_FRAME_DELAY {
+        DEFAULT_FRAME_DELAY
+    } else {
+        delay
+    }
+}
+
+/// Averages `image` over square blocks, `blocks` of them fitting along the longer side.
+pub fn create_pixelated_image(image: &DynamicImage, blocks: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let block = width.max(height).div_ceil(blocks).max(1);
+
+    let mut pixelated = RgbaImage::new(width, height);
+    for block_y in (0..height).step_by(block as usize) {
+        for block_x in (0..width).step_by(block as usize) {
+            let block_width = block.min(width - block_x);
+            let block_height = block.min(height - block_y);
+
+            let mut sum = [0u64; 4];
+            for (_, _, pixel) in image
+                .view(block_x, block_y, block_width, block_height)
+                .pixels()
+            {
+                sum.iter_mut()
+                    .zip(pixel.0)
+                    .for_each(|(s, c)| *s += c as u64);
+            }
+            let count = (block_width * block_height) as u64;
+            let average = Rgba(sum.map(|s| (s / count) as u8));
+
+            for y in block_y..block_y + block_height {
+                for x in block_x..block_x + block_width {
+                    pixelated.put_pixel(x, y, average);
+                }
+            }
+        }
+    }
+    pixelated
+}
+
+/// Rows of the 3x5 digit glyphs used to print tile numbers, most significant bit on the left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+/// Longer side of the image the blurred cover is scaled down to.
+const BLURRED_COVER_SIZE: u32 = 12;
+
+/// What the hidden parts of the image are covered with.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum CoverStyle {
+    Solid,
+    Image,
+    Noise,
+    Blurred,
+    Numbers,
+}
+
+impl CoverStyle {
+    pub const ALL: [CoverStyle; 5] = [
+        CoverStyle::Solid,
+        CoverStyle::Image,
+        CoverStyle::Noise,
+        CoverStyle::Blurred,
+        CoverStyle::Numbers,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoverStyle::Solid => "Solid colour",
+            CoverStyle::Image => "Cover image",
+            CoverStyle::Noise => "Noise",
+            CoverStyle::Blurred => "Blurred answer",
+            CoverStyle::Numbers => "Tile numbers",
+        }
+    }
+}
+
+/// A decoded cover image, shared by every round that uses it. Two covers are the same only if
+/// they are the same decoded image, comparing pixels on every settings change would be slow.
+#[derive(Clone, Debug)]
+pub struct CoverImage(pub Arc<RgbaImage>);
+
+impl PartialEq for CoverImage {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct CoverSettings {
+    pub style: CoverStyle,
+    /// Colour of the solid cover, also behind tile numbers and in place of a missing cover image.
+    pub color: [u8; 3],
+    pub image: Option<CoverImage>,
+    /// Repeats the cover image at its own size instead of stretching it over the whole image.
+    pub tile_image: bool,
+}
+
+/// The cover hiding `original`. Tile numbers are left to whoever knows the tiles, see
+/// `draw_number`, here they only get the solid background.
+pub fn create_cover_image(
+    original: &DynamicImage,
+    settings: &CoverSettings,
+    seed: u64,
+) -> RgbaImage {
+    let (width, height) = original.dimensions();
+    let [r, g, b] = settings.color;
+    let solid = || RgbaImage::from_pixel(width, height, Rgba([r, g, b, 255]));
+
+    match settings.style {
+        CoverStyle::Solid | CoverStyle::Numbers => solid(),
+        CoverStyle::Image => match settings.image.as_ref().map(|image| &*image.0) {
+            Some(cover) if settings.tile_image => RgbaImage::from_fn(width, height, |x, y| {
+                *cover.get_pixel(x % cover.width(), y % cover.height())
+            }),
+            Some(cover) => imageops::resize(cover, width, height, FilterType::Triangle),
+            None => solid(),
+        },
+        CoverStyle::Noise => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            RgbaImage::from_fn(width, height, |_, _| {
+                let value = rng.gen();
+                Rgba([value, value, value, 255])
+            })
+        }
+        CoverStyle::Blurred => {
+            // Scaling far down and back up smears the answer into a few colour blobs.
+            let scale = BLURRED_COVER_SIZE as f32 / width.max(height) as f32;
+            let small_width = ((width as f32 * scale).round() as u32).max(1);
+            let small_height = ((height as f32 * scale).round() as u32).max(1);
+            original
+                .resize_exact(small_width, small_height, FilterType::Triangle)
+                .resize_exact(width, height, FilterType::Triangle)
+                .to_rgba8()
+        }
+    }
+}
+
+/// Prints `number` centred on `center` with digits `height` pixels tall.
+pub fn draw_number(
+    image: &mut RgbaImage,
+    number: usize,
+    (cx, cy): (u32, u32),
+    height: u32,
+    color: Rgba<u8>,
+) {
+    let digits: Vec<usize> = number
+        .to_string()
+        .bytes()
+        .map(|digit| (digit - b'0') as usize)
+        .collect();
+    let scale = (height / 5).max(1);
+    // Every digit is 3 cells wide with 1 cell of space between digits.
+    let text_width = (digits.len() as u32 * 4 - 1) * scale;
+    let left = cx as i64 - text_width as i64 / 2;
+    let top = cy as i64 - (5 * scale) as i64 / 2;
+
+    for (i, &digit) in digits.iter().enumerate() {
+        for (row, bits) in DIGITS[digit].iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+
+                let cell_x = left + ((i as u32 * 4 + column) * scale) as i64;
+                let cell_y = top + (row as u32 * scale) as i64;
+                for y in cell_y..cell_y + scale as i64 {
+                    for x in cell_x..cell_x + scale as i64 {
+                        if x >= 0 && y >= 0 && x < image.width() as i64 && y < image.height() as i64
+                        {
+                            image.put_pixel(x as u32, y as u32, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Black or white, whichever stands out more on `color`.
+pub fn contrasting_color([r, g, b]: [u8; 3]) -> Rgba<u8> {
+    let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luma > 128.0 {
+        Rgba([0, 0, 0, 255])
+    } else {
+        Rgba([255, 255, 255, 255])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::ColorType;
+
+    /// Where pixels (0, 0) and (1, 0) of a 3x2 image end up once upright, for orientations 1
+    /// to 8, with `w` and `h` the upright size.
+    fn expected_corners(orientation: u32, w: u32, h: u32) -> [(u32, u32); 2] {
+        match orientation {
+            1 => [(0, 0), (1, 0)],
+            2 => [(w - 1, 0), (w - 2, 0)],
+            3 => [(w - 1, h - 1), (w - 2, h - 1)],
+            4 => [(0, h - 1), (1, h - 1)],
+            5 => [(0, 0), (0, 1)],
+            6 => [(w - 1, 0), (w - 1, 1)],
+            7 => [(w - 1, h - 1), (w - 1, h - 2)],
+            8 => [(0, h - 1), (0, h - 2)],
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn orientations_turn_images_upright() {
+        let mut stored = RgbaImage::new(3, 2);
+        stored.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        stored.put_pixel(1, 0, Rgba([0, 255, 0, 255]));
+
+        for orientation in 1..=8 {
+            let upright = apply_orientation(DynamicImage::ImageRgba8(stored.clone()), orientation);
+            let (w, h) = upright.dimensions();
+            assert_eq!((w, h), if orientation < 5 { (3, 2) } else { (2, 3) });
+
+            let [red, green] = expected_corners(orientation, w, h);
+            assert_eq!(upright.get_pixel(red.0, red.1), Rgba([255, 0, 0, 255]));
+            assert_eq!(upright.get_pixel(green.0, green.1), Rgba([0, 255, 0, 255]));
+        }
+    }
+
+    /// A 4x2 JPEG with an EXIF segment holding only the orientation.
+    fn jpeg_with_orientation(orientation: u16) -> Vec<u8> {
+        let mut jpeg = Vec::new();
+        JpegEncoder::new(&mut jpeg)
+            .encode(&[128; 4 * 2 * 3], 4, 2, ColorType::Rgb8)
+            .unwrap();
+
+        let mut exif = b"Exif\0\0II*\0\x08\0\0\0\x01\0".to_vec();
+        exif.extend_from_slice(&0x0112u16.to_le_bytes());
+        exif.extend_from_slice(&3u16.to_le_bytes());
+        exif.extend_from_slice(&1u32.to_le_bytes());
+        exif.extend_from_slice(&orientation.to_le_bytes());
+        exif.extend_from_slice(&[0; 2 + 4]);
+
+        let mut segment = vec![0xff, 0xe1];
+        segment.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        segment.extend_from_slice(&exif);
+        jpeg.splice(2..2, segment);
+        jpeg
+    }
+
+    #[test]
+    fn reads_exif_orientation() {
+        for orientation in 1..=8 {
+            let path = std::env::temp_dir().join(format!(
+                "image-guesser-orientation-{}-{orientation}.jpg",
+                std::process::id()
+            ));
+            fs::write(&path, jpeg_with_orientation(orientation)).unwrap();
+            let path = path.to_str().unwrap();
+
+            assert_eq!(read_orientation(path), Some(orientation as u32));
+            let upright = open_image(path).unwrap();
+            assert_eq!(
+                upright.dimensions(),
+                if orientation < 5 { (4, 2) } else { (2, 4) }
+            );
+            fs::remove_file(path).unwrap();
+        }
+    }
+
+    const D50: [f64; 3] = [0.9642, 1.0, 0.8249];
+    const SRGB_COLORANTS: [[f64; 3]; 3] = [
+        [0.4361, 0.2225, 0.0139],
+        [0.3851, 0.7169, 0.0971],
+        [0.1431, 0.0606, 0.7141],
+    ];
+    const P3_COLORANTS: [[f64; 3]; 3] = [
+        [0.5151, 0.2412, -0.0011],
+        [0.2920, 0.6922, 0.0419],
+        [0.1571, 0.0666, 0.7841],
+    ];
+
+    fn xyz_tag(xyz: [f64; 3]) -> Vec<u8> {
+        let mut tag = b"XYZ \0\0\0\0".to_vec();
+        for value in xyz {
+            tag.extend_from_slice(&((value * 65536.0).round() as i32).to_be_bytes());
+        }
+        tag
+    }
+
+    /// A `curv` tag with the given points, none is linear and one is a gamma.
+    fn curv(points: &[u16]) -> Vec<u8> {
+        let mut tag = b"curv\0\0\0\0".to_vec();
+        tag.extend_from_slice(&(points.len() as u32).to_be_bytes());
+        for point in points {
+            tag.extend_from_slice(&point.to_be_bytes());
+        }
+        tag
+    }
+
+    /// A `para` tag of function type 0, a plain gamma.
+    fn para_gamma(gamma: f64) -> Vec<u8> {
+        let mut tag = b"para\0\0\0\0\0\0\0\0".to_vec();
+        tag.extend_from_slice(&((gamma * 65536.0).round() as i32).to_be_bytes());
+        tag
+    }
+
+    /// A minimal RGB display profile with the given colorants and tone curve for every channel.
+    fn rgb_profile(colorants: [[f64; 3]; 3], trc: Vec<u8>) -> Vec<u8> {
+        let [red, green, blue] = colorants;
+        icc_profile(
+            b"RGB ",
+            &[
+                (b"wtpt", xyz_tag(D50)),
+                (b"rXYZ", xyz_tag(red)),
+                (b"gXYZ", xyz_tag(green)),
+                (b"bXYZ", xyz_tag(blue)),
+                (b"rTRC", trc.clone()),
+                (b"gTRC", trc.clone()),
+                (b"bTRC", trc),
+            ],
+        )
+    }
+
+    /// A display profile of the given colour space holding `tags`.
+    fn icc_profile(color_space: &[u8; 4], tags: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+        let mut data = Vec::new();
+        let data_start = 128 + 4 + 12 * tags.len();
+        for (signature, tag) in tags {
+            table.extend_from_slice(*signature);
+            table.extend_from_slice(&((data_start + data.len()) as u32).to_be_bytes());
+            table.extend_from_slice(&(tag.len() as u32).to_be_bytes());
+            data.extend_from_slice(tag);
+            data.resize(data.len().div_ceil(4) * 4, 0);
+        }
+
+        let mut header = vec![0; 128];
+        let size = (128 + table.len() + data.len()) as u32;
+        header[0..4].copy_from_slice(&size.to_be_bytes());
+        header[8..12].copy_from_slice(&0x0210_0000u32.to_be_bytes());
+        header[12..16].copy_from_slice(b"mntr");
+        header[16..20].copy_from_slice(color_space);
+        header[20..24].copy_from_slice(b"XYZ ");
+        header[36..40].copy_from_slice(b"acsp");
+        header.extend(table);
+        header.extend(data);
+        header
+    }
+
+    fn convert(profile: &[u8], pixel: [u8; 3]) -> [u8; 3] {
+        let transform = srgb_transform(profile).expect("Profile is valid.");
+        let mut data = [pixel[0], pixel[1], pixel[2], 255];
+        transform.apply(&mut data);
+        assert_eq!(data[3], 255);
+        [data[0], data[1], data[2]]
+    }
+
+    fn assert_close(actual: [u8; 3], expected: [u8; 3]) {
+        let close = actual
+            .iter()
+            .zip(expected)
+            .all(|(&a, e)| (a as i32 - e as i32).abs() <= 2);
+        assert!(close, "{actual:?} is not close to {expected:?}");
+    }
+
+    #[test]
+    fn converts_linear_curves_to_srgb() {
+        for trc in [curv(&[]), curv(&[0x0100]), para_gamma(1.0)] {
+            let profile = rgb_profile(SRGB_COLORANTS, trc);
+            assert_close(convert(&profile, [255, 255, 255]), [255, 255, 255]);
+            assert_close(convert(&profile, [0, 0, 0]), [0, 0, 0]);
+            // Linear 0.5 is 188 in sRGB.
+            assert_close(convert(&profile, [128, 128, 128]), [188, 188, 188]);
+        }
+    }
+
+    #[test]
+    fn converts_gamma_curves_to_srgb() {
+        for trc in [curv(&[0x0233]), para_gamma(2.2)] {
+            let profile = rgb_profile(SRGB_COLORANTS, trc);
+            // The sRGB curve is close to a gamma of 2.2.
+            let grey = convert(&profile, [128, 128, 128]);
+            assert!(grey.iter().all(|&c| (125..=131).contains(&c)), "{grey:?}");
+        }
+    }
+
+    #[test]
+    fn converts_wide_gamut_colours_to_srgb() {
+        let srgb = rgb_profile(SRGB_COLORANTS, curv(&[]));
+        let p3 = rgb_profile(P3_COLORANTS, curv(&[]));
+        // The same numbers are a more saturated colour in Display P3.
+        let colour = [200, 100, 100];
+        let in_srgb = convert(&srgb, colour);
+        let in_p3 = convert(&p3, colour);
+        assert!(in_p3[0] > in_srgb[0], "{in_p3:?} {in_srgb:?}");
+        assert!(in_p3[1] < in_srgb[1], "{in_p3:?} {in_srgb:?}");
+        assert_close(convert(&p3, [255, 0, 0]), [255, 0, 0]);
+    }
+
+    #[test]
+    fn leaves_grey_and_cmyk_profiles_alone() {
+        let grey = icc_profile(
+            b"GRAY",
+            &[(b"wtpt", xyz_tag(D50)), (b"kTRC", curv(&[0x0100]))],
+        );
+        assert!(Profile::new_from_slice(&grey, false).is_some());
+        assert!(srgb_transform(&grey).is_none());
+
+        let cmyk = icc_profile(b"CMYK", &[(b"wtpt", xyz_tag(D50))]);
+        assert!(srgb_transform(&cmyk).is_none());
+    }
+
+    #[test]
+    fn ignores_invalid_profiles() {
+        assert!(srgb_transform(b"not a profile").is_none());
+        let mut truncated = rgb_profile(SRGB_COLORANTS, curv(&[]));
+        truncated.truncate(200);
+        assert!(srgb_transform(&truncated).is_none());
+    }
+}
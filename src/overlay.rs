@@ -0,0 +1,128 @@
+use egui::{pos2, vec2, Align2, Color32, FontId, Painter, Pos2, Rect, Stroke};
+
+use crate::tiles::{tile_label, Tile};
+
+/// Height of the labels relative to the shorter side of the tile.
+const LABEL_SIZE: f32 = 0.3;
+const LABEL_COLOR: Color32 = Color32::WHITE;
+const LABEL_SHADOW: Color32 = Color32::BLACK;
+const GRID_STROKE: Stroke = Stroke {
+    width: 1.0,
+    color: Color32::from_rgba_premultiplied(160, 160, 160, 160),
+};
+
+struct LabeledTile {
+    label: String,
+    /// Bounding box of the tile, relative to the frame size.
+    uv: Rect,
+    tile: Tile,
+}
+
+/// Grid lines and tile names drawn over the frame, so the audience can ask for a tile the host
+/// can reveal by name. Filled in by the worker, drawn by the windows showing the frame.
+pub struct TileOverlay {
+    pub enabled: bool,
+    tiles: Vec<LabeledTile>,
+    covered: Vec<bool>,
+    /// Size of the frame the tiles were cut from.
+    width: u32,
+    height: u32,
+}
+
+impl TileOverlay {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            tiles: vec![],
+            covered: vec![],
+            width: 0,
+            height: 0,
+        }
+    }
+
+    /// Labels `tiles` of a `width` by `height` frame, in the order of the covered flags.
+    pub fn set_tiles(&mut self, tiles: &[Tile], width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        let (width, height) = (width as f32, height as f32);
+        self.tiles = tiles
+            .iter()
+            .map(|tile| LabeledTile {
+                label: tile_label(tile.row, tile.column),
+                uv: Rect::from_min_max(
+                    pos2(tile.x as f32 / width, tile.y as f32 / height),
+                    pos2(
+                        (tile.x + tile.width) as f32 / width,
+                        (tile.y + tile.height) as f32 / height,
+                    ),
+                ),
+                tile: tile.clone(),
+            })
+            .collect();
+    }
+
+    pub fn set_covered(&mut self, covered: &[bool]) {
+        self.covered = covered.to_vec();
+    }
+
+    /// The tile at `uv`, relative to the frame size, as `(row, column)`.
+    pub fn tile_at(&self, uv: Pos2) -> Option<(u8, u8)> {
+        let x = ((uv.x * self.width as f32) as u32).min(self.width.saturating_sub(1));
+        let y = ((uv.y * self.height as f32) as u32).min(self.height.saturating_sub(1));
+        self.tiles
+            .iter()
+            .find(|labeled| labeled.tile.contains(x, y))
+            .map(|labeled| (labeled.tile.row, labeled.tile.column))
+    }
+
+    /// Center of the bounding box of the tile at `(row, column)`, relative to the frame size.
+    pub fn tile_center(&self, row: u8, column: u8) -> Option<Pos2> {
+        self.tiles
+            .iter()
+            .find(|labeled| (labeled.tile.row, labeled.tile.column) == (row, column))
+            .map(|labeled| labeled.uv.center())
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.covered.clear();
+    }
+
+    /// Draws the grid over the frame shown in `rect` and names the tiles that are still covered.
+    pub fn paint(&self, painter: &Painter, rect: Rect) {
+        if !self.enabled {
+            return;
+        }
+
+        for (tile, &covered) in self.tiles.iter().zip(&self.covered) {
+            let region = Rect::from_min_max(
+                rect.lerp(tile.uv.min.to_vec2()),
+                rect.lerp(tile.uv.max.to_vec2()),
+            );
+            // Only plain rectangles line up into a grid.
+            if tile.tile.mask.is_none() {
+                painter.rect_stroke(region, 0.0, GRID_STROKE);
+            }
+            if !covered {
+                continue;
+            }
+
+            let font = FontId::proportional(region.width().min(region.height()) * LABEL_SIZE);
+            let center = region.center();
+            painter.text(
+                center + vec2(1.0, 1.0),
+                Align2::CENTER_CENTER,
+                &tile.label,
+                font.clone(),
+                LABEL_SHADOW,
+            );
+            painter.text(
+                center,
+                Align2::CENTER_CENTER,
+                &tile.label,
+                font,
+                LABEL_COLOR,
+            );
+        }
+    }
+}
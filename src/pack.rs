@@ -0,0 +1,334 @@
+use std::fs;
+use std::io;
+use std::num::NonZeroU8;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::reveal::RevealMode;
+
+/// Name of the manifest inside the pack folder.
+pub const MANIFEST_NAME: &str = "pack.toml";
+
+/// What the audience has to guess in a round and what it is worth.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Question {
+    pub answer: String,
+    /// Other answers that count as right.
+    pub alternates: Vec<String>,
+    /// Hints for the host to give out, in order.
+    pub hints: Vec<String>,
+    pub category: String,
+    pub points: u32,
+}
+
+impl Question {
+    /// The question with blank alternates and hints left while editing dropped.
+    pub fn trimmed(&self) -> Self {
+        let trim = |items: &[String]| {
+            items
+                .iter()
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+        Self {
+            answer: self.answer.trim().to_string(),
+            alternates: trim(&self.alternates),
+            hints: trim(&self.hints),
+            category: self.category.trim().to_string(),
+            points: self.points,
+        }
+    }
+}
+
+/// One round of a pack. Settings left out are whatever the host has picked.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct PackImage {
+    /// Path of the image relative to the pack folder.
+    pub file: String,
+    pub question: Question,
+    pub rows: Option<u8>,
+    pub columns: Option<u8>,
+    pub mode: Option<RevealMode>,
+}
+
+/// A folder with the images of a show and a manifest listing them with their answers, in
+/// the order they are played. The manifest is TOML with one `[[image]]` table per round, only
+/// `file` is required:
+///
+/// ```toml
+/// [[image]]
+/// file = "cat.png"
+/// answer = "Cat"
+/// alternates = ["Kitten"]
+/// hints = ["It purrs"]
+/// category = "Animals"
+/// points = 100
+/// rows = 4
+/// columns = 6
+/// mode = "Tiles"
+/// ```
+pub struct QuizPack {
+    pub images: Vec<PackImage>,
+}
+
+impl QuizPack {
+    pub fn load(folder: &str) -> Result<Self, String> {
+        let path = Path::new(folder).join(MANIFEST_NAME);
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("Could not read {}: {err}", path.display()))?;
+        parse_manifest(&text).map_err(|err| format!("{}: {err}", path.display()))
+    }
+}
+
+/// Writes a pack of the images at the given paths to `folder`. The images are copied into
+/// the folder along with their saved tile orders, `file` of every image is filled in here.
+/// Nothing in the folder is overwritten, copies whose name is taken get a number in front.
+pub fn save_pack(folder: &str, images: Vec<(String, PackImage)>) -> io::Result<()> {
+    fs::create_dir_all(folder)?;
+    let folder = fs::canonicalize(folder)?;
+    let sources: Vec<PathBuf> = images
+        .iter()
+        .map(|(source, _)| canonical(Path::new(source)))
+        .collect();
+
+    let mut pack = QuizPack { images: vec![] };
+    // Files of the pack so far and the images they came from.
+    let mut saved: Vec<(String, &PathBuf)> = vec![];
+    for ((source, mut image), canonical_source) in images.into_iter().zip(&sources) {
+        let name = Path::new(&source)
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, source.clone()))?
+            .to_string_lossy()
+            .into_owned();
+
+        if let Some((file, _)) = saved.iter().find(|(_, saved)| *saved == canonical_source) {
+            image.file = file.clone();
+        } else if canonical_source.parent() == Some(&*folder) {
+            // Images already in the folder stay where they are.
+            image.file = name;
+        } else {
+            image.file = free_name(&folder, &name, &sources);
+            let target = folder.join(&image.file);
+            fs::copy(&source, &target)?;
+            let order = format!("{source}.order");
+            if Path::new(&order).is_file() {
+                fs::copy(&order, format!("{}.order", target.display()))?;
+            }
+        }
+        saved.push((image.file.clone(), canonical_source));
+        pack.images.push(image);
+    }
+
+    let text =
+        format_manifest(&pack).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    fs::write(folder.join(MANIFEST_NAME), text)
+}
+
+/// `path` with links and relative parts resolved, as far as it exists.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The first of `name`, `2-name`, `3-name` and so on that is free in `folder`: no file or tile
+/// order has that name and none of the images of the pack `sources` lives there.
+fn free_name(folder: &Path, name: &str, sources: &[PathBuf]) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => name.to_string(),
+            n => format!("{n}-{name}"),
+        })
+        .find(|file| {
+            let target = folder.join(file);
+            let order = format!("{}.order", target.display());
+            !target.exists() && !Path::new(&order).exists() && !sources.contains(&target)
+        })
+        .expect("Some number is free.")
+}
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    image: Vec<ManifestImage>,
+}
+
+/// A `[[image]]` table, `PackImage` with the question flattened into it.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestImage {
+    file: String,
+    #[serde(default)]
+    answer: String,
+    #[serde(default)]
+    alternates: Vec<String>,
+    #[serde(default)]
+    hints: Vec<String>,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    points: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rows: Option<NonZeroU8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    columns: Option<NonZeroU8>,
+    /// Name of the reveal mode, in any case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,
+}
+
+fn parse_manifest(text: &str) -> Result<QuizPack, String> {
+    let manifest: Manifest = toml::from_str(text).map_err(|err| err.to_string())?;
+    let images = manifest
+        .image
+        .into_iter()
+        .enumerate()
+        .map(|(idx, image)| {
+            let error = |message: &str| format!("image {}: {message}", idx + 1);
+            if image.file.is_empty() {
+                return Err(error("no file"));
+            }
+            let mode = match image.mode {
+                Some(mode) => Some(
+                    RevealMode::ALL
+                        .into_iter()
+                        .find(|m| m.name().eq_ignore_ascii_case(&mode))
+                        .ok_or_else(|| error(&format!("unknown reveal mode `{mode}`")))?,
+                ),
+                None => None,
+            };
+            Ok(PackImage {
+                file: image.file,
+                question: Question {
+                    answer: image.answer,
+                    alternates: image.alternates,
+                    hints: image.hints,
+                    category: image.category,
+                    points: image.points,
+                },
+                rows: image.rows.map(NonZeroU8::get),
+                columns: image.columns.map(NonZeroU8::get),
+                mode,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(QuizPack { images })
+}
+
+fn format_manifest(pack: &QuizPack) -> Result<String, toml::ser::Error> {
+    let manifest = Manifest {
+        image: pack
+            .images
+            .iter()
+            .map(|image| ManifestImage {
+                file: image.file.clone(),
+                answer: image.question.answer.clone(),
+                alternates: image.question.alternates.clone(),
+                hints: image.question.hints.clone(),
+                category: image.question.category.clone(),
+                points: image.question.points,
+                rows: image.rows.and_then(NonZeroU8::new),
+                columns: image.columns.and_then(NonZeroU8::new),
+                mode: image.mode.map(|mode| mode.name().to_string()),
+            })
+            .collect(),
+    };
+    toml::to_string(&manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_round_trip() {
+        let pack = QuizPack {
+            images: vec![
+                PackImage {
+                    file: "cat \"1\".png".to_string(),
+                    question: Question {
+                        answer: "Cat".to_string(),
+                        alternates: vec!["Kitten".to_string(), "Felis, catus".to_string()],
+                        hints: vec!["It purrs\nloudly".to_string()],
+                        category: "Animals".to_string(),
+                        points: 100,
+                    },
+                    rows: Some(4),
+                    columns: Some(6),
+                    mode: Some(RevealMode::Tone),
+                },
+                PackImage {
+                    file: "dog.jpg".to_string(),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let text = format_manifest(&pack).unwrap();
+        assert_eq!(parse_manifest(&text).unwrap().images, pack.images);
+    }
+
+    #[test]
+    fn parses_literal_strings_and_multi_line_arrays() {
+        let text = r#"
+            # First round
+            [[image]]
+            file = 'C:\pictures\cat.png'
+            answer = "Cat"
+            alternates = [
+                "Kitten",
+                'Tom, the cat', # trailing comma and comments
+            ]
+            mode = "silhouette to colour"
+        "#;
+
+        let images = parse_manifest(text).unwrap().images;
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].file, "C:\\pictures\\cat.png");
+        assert_eq!(images[0].question.alternates, ["Kitten", "Tom, the cat"]);
+        assert_eq!(images[0].mode, Some(RevealMode::Tone));
+        assert_eq!(images[0].rows, None);
+    }
+
+    #[test]
+    fn saving_never_overwrites_files() {
+        let root = std::env::temp_dir().join(format!("image-guesser-pack-{}", std::process::id()));
+        let (a, b) = (root.join("a"), root.join("b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        fs::write(a.join("cat.png"), "a").unwrap();
+        fs::write(a.join("2-cat.png"), "unrelated").unwrap();
+        fs::write(b.join("cat.png"), "b").unwrap();
+        fs::write(b.join("cat.png.order"), "0,0\n").unwrap();
+
+        let entry = |folder: &Path| {
+            let source = folder.join("cat.png").to_string_lossy().into_owned();
+            (source, PackImage::default())
+        };
+        let images = vec![entry(&b), entry(&a), entry(&b)];
+        save_pack(a.to_str().unwrap(), images).unwrap();
+
+        let read = |file: &str| fs::read_to_string(a.join(file)).unwrap();
+        assert_eq!(read("cat.png"), "a");
+        assert_eq!(read("2-cat.png"), "unrelated");
+        assert_eq!(read("3-cat.png"), "b");
+        assert_eq!(read("3-cat.png.order"), "0,0\n");
+        let pack = QuizPack::load(a.to_str().unwrap()).unwrap();
+        let files: Vec<&str> = pack.images.iter().map(|image| &*image.file).collect();
+        assert_eq!(files, ["3-cat.png", "cat.png", "3-cat.png"]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_images() {
+        let parse = |image: &str| parse_manifest(&format!("[[image]]\n{image}"));
+        assert!(parse("answer = \"Cat\"").is_err());
+        assert!(parse("file = \"\"").is_err());
+        assert!(parse("file = \"cat.png\"\nrows = 0").is_err());
+        assert!(parse("file = \"cat.png\"\ncolumns = 256").is_err());
+        assert!(parse("file = \"cat.png\"\nmode = \"Fade\"").is_err());
+        assert!(parse("file = \"cat.png\"\nanswr = \"Cat\"").is_err());
+        assert!(parse("file = \"cat.png\"").is_ok());
+    }
+}
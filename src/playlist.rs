@@ -0,0 +1,222 @@
+use std::fs;
+use std::path::Path;
+
+use egui::TextureHandle;
+use image::{DynamicImage, ImageFormat, ImageResult};
+
+use crate::my_image::{is_svg, open_image};
+use crate::pack::{PackImage, Question};
+use crate::reveal::RevealMode;
+use crate::tiles::TileSettings;
+
+/// Longest side of the thumbnails shown in the playlist.
+pub const THUMBNAIL_SIZE: u32 = 96;
+
+pub struct PlaylistEntry {
+    pub path: String,
+    pub thumbnail: TextureHandle,
+    /// Tiles and mode the image is played with, kept when the host changes them during its
+    /// round.
+    pub tiles: TileSettings,
+    pub mode: RevealMode,
+    pub question: Question,
+}
+
+impl PlaylistEntry {
+    /// File name of the image, falls back to the whole path.
+    pub fn name(&self) -> &str {
+        Path::new(&self.path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.path)
+    }
+
+    /// The entry as a round of a pack, `file` is left for `save_pack` to fill in.
+    pub fn pack_image(&self) -> PackImage {
+        PackImage {
+            file: String::new(),
+            question: self.question.trimmed(),
+            rows: Some(self.tiles.rows),
+            columns: Some(self.tiles.columns),
+            mode: Some(self.mode),
+        }
+    }
+}
+
+/// Images queued up for the next rounds, played in order.
+#[derive(Default)]
+pub struct Playlist {
+    pub entries: Vec<PlaylistEntry>,
+    /// Entry of the image that is loaded, if it came from the playlist.
+    pub current: Option<usize>,
+}
+
+impl Playlist {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.current = None;
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut PlaylistEntry> {
+        self.entries.get_mut(self.current?)
+    }
+
+    /// Index of the entry after the current one, the first one when nothing is playing yet.
+    pub fn next(&self) -> Option<usize> {
+        let next = self.current.map_or(0, |current| current + 1);
+        (next < self.entries.len()).then_some(next)
+    }
+
+    pub fn previous(&self) -> Option<usize> {
+        self.current?.checked_sub(1)
+    }
+
+    /// Swaps the entry at `idx` with the one before it, the current image stays current.
+    pub fn move_up(&mut self, idx: usize) {
+        if idx == 0 || idx >= self.entries.len() {
+            return;
+        }
+
+        self.entries.swap(idx - 1, idx);
+        self.current = self.current.map(|current| match current {
+            c if c == idx => idx - 1,
+            c if c == idx - 1 => idx,
+            c => c,
+        });
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        self.entries.remove(idx);
+        self.current = match self.current {
+            Some(current) if current == idx => None,
+            Some(current) if current > idx => Some(current - 1),
+            current => current,
+        };
+    }
+}
+
+/// Images in `folder` the image crate can open, sorted by path.
+pub fn list_images(folder: &str) -> Vec<String> {
+    let Ok(dir) = fs::read_dir(folder) else {
+        return vec![];
+    };
+
+    let mut paths: Vec<String> = dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let readable = ImageFormat::from_path(path).is_ok_and(|f| f.reading_enabled());
+            path.is_file() && (readable || is_svg(path))
+        })
+        .filter_map(|path| path.to_str().map(String::from))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Decodes the image at `path` and shrinks it down for the playlist.
+pub fn load_thumbnail(path: &str) -> ImageResult<DynamicImage> {
+    let image = open_image(path)?;
+    Ok(image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::Tiling;
+    use egui::{Color32, ColorImage, Context, TextureFilter};
+
+    /// A playlist of `len` entries named by their starting place.
+    fn playlist(len: usize, current: Option<usize>) -> Playlist {
+        let ctx = Context::default();
+        let thumbnail = ctx.load_texture(
+            "thumbnail",
+            ColorImage::new([1, 1], Color32::BLACK),
+            TextureFilter::Linear,
+        );
+        let entries = (0..len)
+            .map(|idx| PlaylistEntry {
+                path: idx.to_string(),
+                thumbnail: thumbnail.clone(),
+                tiles: TileSettings {
+                    rows: 4,
+                    columns: 4,
+                    tiling: Tiling::Grid,
+                    voronoi_cells: 32,
+                },
+                mode: RevealMode::Tiles,
+                question: Question::default(),
+            })
+            .collect();
+        Playlist { entries, current }
+    }
+
+    fn paths(playlist: &Playlist) -> Vec<&str> {
+        playlist.entries.iter().map(|entry| &*entry.path).collect()
+    }
+
+    #[test]
+    fn moving_up_keeps_the_current_entry() {
+        let mut list = playlist(3, Some(1));
+        list.move_up(0);
+        list.move_up(3);
+        assert_eq!(paths(&list), ["0", "1", "2"]);
+        assert_eq!(list.current, Some(1));
+
+        list.move_up(1);
+        assert_eq!(paths(&list), ["1", "0", "2"]);
+        assert_eq!(list.current, Some(0));
+
+        list.move_up(2);
+        assert_eq!(paths(&list), ["1", "2", "0"]);
+        assert_eq!(list.current, Some(0));
+
+        list.move_up(1);
+        assert_eq!(paths(&list), ["2", "1", "0"]);
+        assert_eq!(list.current, Some(1));
+
+        let mut list = playlist(2, None);
+        list.move_up(1);
+        assert_eq!(list.current, None);
+    }
+
+    #[test]
+    fn removing_keeps_the_current_entry() {
+        let mut list = playlist(4, Some(2));
+        list.remove(3);
+        assert_eq!(list.current, Some(2));
+        list.remove(0);
+        assert_eq!(paths(&list), ["1", "2"]);
+        assert_eq!(list.current, Some(1));
+        list.remove(1);
+        assert_eq!(paths(&list), ["1"]);
+        assert_eq!(list.current, None);
+
+        let mut list = playlist(2, None);
+        list.remove(0);
+        assert_eq!(list.current, None);
+    }
+
+    #[test]
+    fn steps_to_neighbouring_entries() {
+        let list = playlist(3, None);
+        assert_eq!(list.next(), Some(0));
+        assert_eq!(list.previous(), None);
+
+        let list = playlist(3, Some(0));
+        assert_eq!(list.next(), Some(1));
+        assert_eq!(list.previous(), None);
+
+        let list = playlist(3, Some(2));
+        assert_eq!(list.next(), None);
+        assert_eq!(list.previous(), Some(1));
+
+        let list = playlist(0, None);
+        assert_eq!(list.next(), None);
+        assert_eq!(list.previous(), None);
+    }
+}
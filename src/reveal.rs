@@ -0,0 +1,253 @@
+use enum_dispatch::enum_dispatch;
+use image::{DynamicImage, RgbaImage};
+
+use crate::compositor::TileMap;
+use crate::my_image::CoverSettings;
+use crate::tiles::{RevealOrder, Tile, TileSettings};
+
+mod blur;
+mod pixelate;
+mod spotlight;
+mod tiles;
+mod tone;
+mod zoom;
+
+pub use self::tiles::{format_tile_order, parse_tile_order, Tiles};
+pub use blur::Blur;
+pub use pixelate::{format_pixelate_steps, parse_pixelate_steps, Pixelate};
+pub use spotlight::Spotlight;
+pub use tone::Tone;
+pub use zoom::Zoom;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum RevealMode {
+    Tiles,
+    Blur,
+    Pixelate,
+    Zoom,
+    Spotlight,
+    Tone,
+}
+
+impl RevealMode {
+    pub const ALL: [RevealMode; 6] = [
+        RevealMode::Tiles,
+        RevealMode::Blur,
+        RevealMode::Pixelate,
+        RevealMode::Zoom,
+        RevealMode::Spotlight,
+        RevealMode::Tone,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RevealMode::Tiles => "Tiles",
+            RevealMode::Blur => "Blur",
+            RevealMode::Pixelate => "Pixelate",
+            RevealMode::Zoom => "Zoom",
+            RevealMode::Spotlight => "Spotlight",
+            RevealMode::Tone => "Silhouette to colour",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct RoundSettings {
+    pub mode: RevealMode,
+    /// Seeds every random choice of the round, so the same image, seed and settings
+    /// always play out the same way.
+    pub seed: u64,
+    pub tiles: TileSettings,
+    /// Tiles revealed first, as `(row, column)`, before falling back to random ones.
+    pub tile_order: Vec<(u8, u8)>,
+    pub reveal_order: RevealOrder,
+    /// How strongly random tile picks favour tiles with little detail, from 0 to 1.
+    pub tile_detail_bias: f32,
+    pub blur_steps: u8,
+    /// Number of blocks along the longer side for each pixelated frame, coarsest first.
+    pub pixelate_steps: Vec<u32>,
+    /// Magnification of the first zoomed frame.
+    pub zoom_start: f32,
+    /// How much each reveal zooms out.
+    pub zoom_factor: f32,
+    /// Point to zoom into, relative to the image size. `None` picks a random one every round.
+    pub zoom_focus: Option<(f32, f32)>,
+    /// What hides the parts of the image that are not revealed yet, for tiles and spotlights.
+    pub cover: CoverSettings,
+    /// Spotlight radius relative to the shorter side of the image.
+    pub spotlight_radius: f32,
+    /// Part of the spotlight radius that fades out towards the edge.
+    pub spotlight_softness: f32,
+}
+
+/// Part of the frame that changed and has to be uploaded again.
+#[derive(Clone, Copy, Debug)]
+pub enum FrameChange {
+    Full,
+    Region {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+}
+
+/// Everything besides the original that the host view of a strategy shows, see `host_image`.
+/// Equal views draw the same image, so the host view is only uploaded again when it changes.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum HostView {
+    Original,
+    /// Outlines of the tiles cut with these settings and seed, numbered when `numbered`.
+    Tiles {
+        settings: TileSettings,
+        seed: u64,
+        numbered: bool,
+    },
+}
+
+/// A way of gradually showing the original image. The strategy owns the frame shown to the
+/// audience and reports which part of it changed after each reveal.
+#[enum_dispatch]
+pub trait RevealStrategy {
+    /// Starts over on `original` with nothing revealed.
+    fn reset(&mut self, original: &DynamicImage);
+
+    /// Reveals a bit more, `None` if there was nothing left to reveal.
+    fn step(&mut self, original: &DynamicImage) -> Option<FrameChange>;
+
+    /// Reveals whatever is at the given pixel, for strategies that have a notion of place.
+    /// Strategies that can cover things again may do so when it is already revealed.
+    fn reveal_at(&mut self, _original: &DynamicImage, _x: u32, _y: u32) -> Option<FrameChange> {
+        None
+    }
+
+    /// Reveals the tile at the given grid position, for strategies that have tiles.
+    /// Revealed tiles may be covered again.
+    fn reveal_tile_at(
+        &mut self,
+        _original: &DynamicImage,
+        _row: u8,
+        _column: u8,
+    ) -> Option<FrameChange> {
+        None
+    }
+
+    /// Replaces the tiles revealed first, as `(row, column)`, for strategies that have tiles.
+    fn set_tile_order(&mut self, _order: Vec<(u8, u8)>) {}
+
+    /// Reveals everything that is left at once.
+    fn reveal_all(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        let mut change = None;
+        while self.step(original).is_some() {
+            change = Some(FrameChange::Full);
+        }
+        change
+    }
+
+    /// Takes back the last reveal, for strategies that keep a history.
+    fn undo(&mut self, _original: &DynamicImage) -> Option<FrameChange> {
+        None
+    }
+
+    /// Brings back the last reveal taken back by `undo`.
+    fn redo(&mut self, _original: &DynamicImage) -> Option<FrameChange> {
+        None
+    }
+
+    /// Lets the strategy react to the display changing its aspect ratio.
+    fn display_resized(&mut self, _original: &DynamicImage, _aspect: f32) -> Option<FrameChange> {
+        None
+    }
+
+    /// Redraws the frame after `original` moved on to the next frame of an animation, with
+    /// whatever was revealed still revealed.
+    fn original_changed(&mut self, original: &DynamicImage) -> Option<FrameChange>;
+
+    fn is_complete(&self) -> bool;
+
+    /// The frame currently shown to the audience. Strategies composited on the GPU, see
+    /// `tile_map`, do not draw it and only keep its size.
+    fn frame(&self) -> &RgbaImage;
+
+    /// Which tile every pixel belongs to, for strategies whose frame is the original with some
+    /// tiles covered. Such frames are composited on the GPU instead of uploaded.
+    fn tile_map(&self) -> Option<TileMap> {
+        None
+    }
+
+    /// What the covered tiles of `tile_map` show.
+    fn cover(&self) -> Option<&RgbaImage> {
+        None
+    }
+
+    /// Which tiles of `tile_map` are covered.
+    fn covered_tiles(&self) -> Option<&[bool]> {
+        None
+    }
+
+    /// The tiles of `tile_map`, in the same order as `covered_tiles`.
+    fn tiles(&self) -> Option<&[Tile]> {
+        None
+    }
+
+    /// What the region of the last `FrameChange::Region` showed before the change, transparent
+    /// where nothing changed, so the change can be animated.
+    fn replaced(&self, _original: &DynamicImage) -> Option<RgbaImage> {
+        None
+    }
+
+    /// The image shown to the host in Full mode right after a reset.
+    fn host_image(&self, original: &DynamicImage) -> DynamicImage {
+        original.clone()
+    }
+
+    /// What `host_image` draws over the original.
+    fn host_view(&self) -> HostView {
+        HostView::Original
+    }
+}
+
+#[enum_dispatch(RevealStrategy)]
+pub enum Strategy {
+    Tiles,
+    Blur,
+    Pixelate,
+    Zoom,
+    Spotlight,
+    Tone,
+}
+
+impl Strategy {
+    /// The strategy picked in `settings`, not yet reset onto an image.
+    pub fn new(settings: &RoundSettings, display_aspect: f32) -> Self {
+        match settings.mode {
+            RevealMode::Tiles => Tiles::new(
+                settings.tiles,
+                settings.tile_order.clone(),
+                settings.reveal_order,
+                settings.tile_detail_bias,
+                settings.cover.clone(),
+                settings.seed,
+            )
+            .into(),
+            RevealMode::Blur => Blur::new(settings.blur_steps).into(),
+            RevealMode::Pixelate => Pixelate::new(settings.pixelate_steps.clone()).into(),
+            RevealMode::Zoom => Zoom::new(
+                settings.zoom_start,
+                settings.zoom_factor,
+                settings.zoom_focus,
+                display_aspect,
+                settings.seed,
+            )
+            .into(),
+            RevealMode::Spotlight => Spotlight::new(
+                settings.spotlight_radius,
+                settings.spotlight_softness,
+                settings.cover.clone(),
+                settings.seed,
+            )
+            .into(),
+            RevealMode::Tone => Tone::default().into(),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use image::imageops::FilterType;
+use image::{DynamicImage, RgbaImage};
+
+use super::{FrameChange, RevealStrategy};
+
+/// Blur of the first blurred frame, relative to the shorter side of the image.
+const MAX_BLUR: f32 = 0.05;
+
+/// Shows the whole image blurred, every step lowers the blur until it is sharp.
+pub struct Blur {
+    steps: u8,
+    step: usize,
+    frame: RgbaImage,
+}
+
+impl Blur {
+    pub fn new(steps: u8) -> Self {
+        Self {
+            steps,
+            step: 0,
+            frame: RgbaImage::new(0, 0),
+        }
+    }
+}
+
+impl RevealStrategy for Blur {
+    fn reset(&mut self, original: &DynamicImage) {
+        self.step = 0;
+        self.frame = blur_frame(original, self.step, self.steps).to_rgba8();
+    }
+
+    fn step(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        if self.is_complete() {
+            return None;
+        }
+
+        self.step += 1;
+        self.frame = blur_frame(original, self.step, self.steps).to_rgba8();
+        Some(FrameChange::Full)
+    }
+
+    fn original_changed(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        self.frame = blur_frame(original, self.step, self.steps).to_rgba8();
+        Some(FrameChange::Full)
+    }
+
+    fn is_complete(&self) -> bool {
+        self.step == self.steps as usize
+    }
+
+    fn frame(&self) -> &RgbaImage {
+        &self.frame
+    }
+}
+
+/// Frame `step` of `steps` of the blur reveal, step 0 is the blurriest and `steps` is sharp.
+fn blur_frame(original: &DynamicImage, step: usize, steps: u8) -> DynamicImage {
+    let (width, height) = (original.width(), original.height());
+    let remaining = steps as usize - step;
+    let sigma = MAX_BLUR * width.min(height) as f32 * remaining as f32 / steps as f32;
+    if sigma < 0.5 {
+        return original.clone();
+    }
+
+    // Blurring a downscaled copy looks the same once scaled back up and keeps large radii cheap.
+    let factor = (sigma / 2.0).max(1.0);
+    let small = original.resize_exact(
+        ((width as f32 / factor) as u32).max(1),
+        ((height as f32 / factor) as u32).max(1),
+        FilterType::Triangle,
+    );
+    small
+        .blur(sigma / factor)
+        .resize_exact(width, height, FilterType::Triangle)
+}
@@ -0,0 +1,95 @@
+use image::{DynamicImage, RgbaImage};
+
+use super::{FrameChange, RevealStrategy};
+use crate::my_image::create_pixelated_image;
+
+/// Shows the image as giant pixels, every step moves to the next finer level.
+pub struct Pixelate {
+    /// Number of blocks along the longer side for each level, coarsest first.
+    levels: Vec<u32>,
+    step: usize,
+    frame: RgbaImage,
+}
+
+impl Pixelate {
+    pub fn new(levels: Vec<u32>) -> Self {
+        Self {
+            levels,
+            step: 0,
+            frame: RgbaImage::new(0, 0),
+        }
+    }
+
+    fn render(&mut self, original: &DynamicImage) {
+        self.frame = match self.levels.get(self.step) {
+            Some(&blocks) => create_pixelated_image(original, blocks),
+            None => original.to_rgba8(),
+        };
+    }
+}
+
+impl RevealStrategy for Pixelate {
+    fn reset(&mut self, original: &DynamicImage) {
+        self.step = 0;
+        self.render(original);
+    }
+
+    fn step(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        if self.is_complete() {
+            return None;
+        }
+
+        self.step += 1;
+        self.render(original);
+        Some(FrameChange::Full)
+    }
+
+    fn original_changed(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        self.render(original);
+        Some(FrameChange::Full)
+    }
+
+    fn is_complete(&self) -> bool {
+        self.step == self.levels.len()
+    }
+
+    fn frame(&self) -> &RgbaImage {
+        &self.frame
+    }
+}
+
+/// Parses a comma separated list of block counts, rejecting empty lists and zeroes.
+pub fn parse_pixelate_steps(text: &str) -> Option<Vec<u32>> {
+    let steps = text
+        .split(',')
+        .map(|step| step.trim().parse().ok().filter(|&blocks| blocks > 0))
+        .collect::<Option<Vec<u32>>>()?;
+    (!steps.is_empty()).then_some(steps)
+}
+
+pub fn format_pixelate_steps(steps: &[u32]) -> String {
+    steps
+        .iter()
+        .map(|blocks| blocks.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixelate_steps_round_trip() {
+        let steps = vec![4, 8, 16, 1000];
+        assert_eq!(format_pixelate_steps(&steps), "4, 8, 16, 1000");
+        assert_eq!(
+            parse_pixelate_steps(&format_pixelate_steps(&steps)),
+            Some(steps)
+        );
+        assert_eq!(parse_pixelate_steps(" 3,9 "), Some(vec![3, 9]));
+        for text in ["", " ", "4,0", "4,,8", "4,", "-2", "a"] {
+            assert_eq!(parse_pixelate_steps(text), None, "{text}");
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use image::{DynamicImage, GenericImageView, GrayImage, Luma, RgbaImage};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{FrameChange, RevealStrategy};
+use crate::my_image::{create_cover_image, CoverSettings};
+use crate::transition::changed_pixels;
+
+/// Light at which a pixel shows more of the image than of the cover.
+const HALF_LIT: u8 = 128;
+/// Once no more than this part of the image is less than half lit, the next step lights up the
+/// rest instead of hunting down the slivers left between the spotlights.
+const MAX_HIDDEN: f64 = 0.05;
+
+/// Shows the image only through soft-edged circles, every step adds one more.
+pub struct Spotlight {
+    /// Radius relative to the shorter side of the image.
+    radius: f32,
+    /// Part of the radius that fades out towards the edge.
+    softness: f32,
+    cover_settings: CoverSettings,
+    /// What shows where no spotlight reaches.
+    cover: RgbaImage,
+    seed: u64,
+    rng: StdRng,
+    /// How much of the image shows through at each pixel.
+    mask: GrayImage,
+    /// The region of the frame the last spotlight lit up, before it did, and where it is.
+    before: (RgbaImage, u32, u32),
+    frame: RgbaImage,
+}
+
+impl Spotlight {
+    pub fn new(radius: f32, softness: f32, cover_settings: CoverSettings, seed: u64) -> Self {
+        Self {
+            radius,
+            softness,
+            cover_settings,
+            cover: RgbaImage::new(0, 0),
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            mask: GrayImage::new(0, 0),
+            before: (RgbaImage::new(0, 0), 0, 0),
+            frame: RgbaImage::new(0, 0),
+        }
+    }
+}
+
+impl RevealStrategy for Spotlight {
+    fn reset(&mut self, original: &DynamicImage) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.mask = GrayImage::new(original.width(), original.height());
+        self.cover = create_cover_image(original, &self.cover_settings, self.seed);
+        self.frame = self.cover.clone();
+    }
+
+    /// Aims at a random pixel that is less than half lit, so every step shows something new and
+    /// stepping, like an animated reveal all, ends with everything revealed.
+    fn step(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        if self.is_complete() {
+            return None;
+        }
+        let lights = self.mask.as_raw();
+        let is_dark = |light: &u8| *light < HALF_LIT;
+        let dark = lights.iter().filter(|light| is_dark(light)).count();
+        if dark as f64 <= MAX_HIDDEN * lights.len() as f64 {
+            return self.reveal_all(original);
+        }
+
+        let pick = self.rng.gen_range(0..dark);
+        let (idx, _) = lights
+            .iter()
+            .enumerate()
+            .filter(|(_, light)| is_dark(light))
+            .nth(pick)
+            .expect("There are this many dark pixels.");
+        let width = self.mask.width() as usize;
+        self.reveal_at(original, (idx % width) as u32, (idx / width) as u32)
+    }
+
+    fn reveal_at(&mut self, original: &DynamicImage, x: u32, y: u32) -> Option<FrameChange> {
+        if self.is_complete() {
+            return None;
+        }
+
+        let radius = self.radius * original.width().min(original.height()) as f32;
+        let center = (x as f32 + 0.5, y as f32 + 0.5);
+        let (x, y, width, height) = add_spotlight(&mut self.mask, center, radius, self.softness);
+        let region = (x, y, width, height);
+        let before = image::imageops::crop_imm(&self.frame, x, y, width, height).to_image();
+        self.before = (before, x, y);
+        composite_spotlights(&mut self.frame, original, &self.cover, &self.mask, region);
+
+        Some(FrameChange::Region {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// Random spotlights could take ages to cover every pixel, so the mask is filled instead.
+    fn reveal_all(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        if self.is_complete() {
+            return None;
+        }
+
+        self.mask
+            .pixels_mut()
+            .for_each(|light| *light = Luma([u8::MAX]));
+        self.frame = original.to_rgba8();
+        Some(FrameChange::Full)
+    }
+
+    fn original_changed(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        let (width, height) = self.frame.dimensions();
+        let region = (0, 0, width, height);
+        composite_spotlights(&mut self.frame, original, &self.cover, &self.mask, region);
+        Some(FrameChange::Full)
+    }
+
+    fn replaced(&self, _original: &DynamicImage) -> Option<RgbaImage> {
+        let (before, x, y) = &self.before;
+        Some(changed_pixels(before, &self.frame, (*x, *y)))
+    }
+
+    fn is_complete(&self) -> bool {
+        self.mask.pixels().all(|&Luma([light])| light == u8::MAX)
+    }
+
+    fn frame(&self) -> &RgbaImage {
+        &self.frame
+    }
+}
+
+/// Lights up a soft-edged disc on `mask` and returns the bounding box that changed
+/// as `(x, y, width, height)`. Overlapping spotlights keep the brighter value.
+fn add_spotlight(
+    mask: &mut GrayImage,
+    (cx, cy): (f32, f32),
+    radius: f32,
+    softness: f32,
+) -> (u32, u32, u32, u32) {
+    let (width, height) = mask.dimensions();
+    let min_x = (cx - radius).max(0.0) as u32;
+    let min_y = (cy - radius).max(0.0) as u32;
+    let max_x = ((cx + radius).max(0.0) as u32).min(width - 1);
+    let max_y = ((cy + radius).max(0.0) as u32).min(height - 1);
+    let hard_radius = radius * (1.0 - softness);
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+            let light = if distance <= hard_radius {
+                1.0
+            } else {
+                let t = ((radius - distance) / (radius - hard_radius)).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            };
+
+            let pixel = mask.get_pixel_mut(x, y);
+            pixel.0[0] = pixel.0[0].max((light * 255.0).round() as u8);
+        }
+    }
+
+    (min_x, min_y, max_x + 1 - min_x, max_y + 1 - min_y)
+}
+
+/// Blends `original` over `cover` by `mask` inside the given bounding box.
+fn composite_spotlights(
+    partial: &mut RgbaImage,
+    original: &DynamicImage,
+    cover: &RgbaImage,
+    mask: &GrayImage,
+    (x, y, width, height): (u32, u32, u32, u32),
+) {
+    for py in y..y + height {
+        for px in x..x + width {
+            let Luma([light]) = *mask.get_pixel(px, py);
+            let mut pixel = original.get_pixel(px, py);
+            let hidden = cover.get_pixel(px, py);
+            for (c, h) in pixel.0.iter_mut().zip(hidden.0).take(3) {
+                *c = ((*c as u32 * light as u32 + h as u32 * (255 - light as u32)) / 255) as u8;
+            }
+            pixel.0[3] = 255;
+            partial.put_pixel(px, py, pixel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::my_image::CoverStyle;
+
+    #[test]
+    fn stepping_reveals_everything() {
+        let original = DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            60,
+            40,
+            image::Rgba([200, 100, 50, 255]),
+        ));
+        let cover = CoverSettings {
+            style: CoverStyle::Solid,
+            color: [0, 0, 0],
+            image: None,
+            tile_image: false,
+        };
+
+        for softness in [0.0, 0.3, 1.0] {
+            let mut spotlight = Spotlight::new(0.1, softness, cover.clone(), 9);
+            spotlight.reset(&original);
+            let steps = (0..1000)
+                .take_while(|_| spotlight.step(&original).is_some())
+                .count();
+            assert!(steps < 1000, "{softness}");
+            assert!(spotlight.is_complete());
+            assert_eq!(spotlight.frame(), &original.to_rgba8());
+        }
+    }
+}
@@ -0,0 +1,521 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{FrameChange, HostView, RevealStrategy};
+use crate::compositor::{TileMap, MAX_TILES};
+use crate::my_image::{
+    contrasting_color, create_cover_image, draw_number, CoverSettings, CoverStyle,
+};
+use crate::tiles::{
+    cover_tile, gen_tiles, order_tiles, outline_tiles, reveal_tile, tile_detail, tile_ids,
+    RevealOrder, Tile, TileData, TileSettings, Tiling,
+};
+
+const OUTLINE_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const COVER_OUTLINE_COLOR: Rgba<u8> = Rgba([96, 96, 96, 255]);
+/// Height of printed tile numbers relative to the shorter side of the tile.
+const NUMBER_SIZE: f32 = 0.4;
+/// At full bias the most detailed tile is `e^DETAIL_BIAS_SCALE` times less likely to be picked
+/// than the least detailed one.
+const DETAIL_BIAS_SCALE: f64 = 10.0;
+
+/// Covers the image with tiles and uncovers one every step, following the scripted order first
+/// and the reveal order after it. Random picks can favour tiles with little detail.
+/// Every reveal and re-cover goes into a history that can be undone and redone.
+pub struct Tiles {
+    settings: TileSettings,
+    /// Tiles to reveal first, as `(row, column)`.
+    order: Vec<(u8, u8)>,
+    /// Order of the tiles left after the scripted ones.
+    reveal_order: RevealOrder,
+    /// `order` followed by `reveal_order`, steps reveal the first tile in it that is covered.
+    queue: Vec<(u8, u8)>,
+    /// Entries of `queue` before this one are revealed or no longer exist. Covering a tile
+    /// again rewinds it, so tiles covered by undo keep their place in the queue.
+    cursor: usize,
+    /// 0 picks tiles uniformly, 1 almost always picks the least detailed tile left.
+    detail_bias: f32,
+    /// Chance of picking each tile relative to the others, empty when `detail_bias` is 0.
+    weights: Vec<f64>,
+    seed: u64,
+    rng: StdRng,
+    tile_data: TileData,
+    /// Index into the tiles of every `(row, column)`.
+    index: HashMap<(u8, u8), usize>,
+    /// Row-major tile of every pixel, empty when there are too many tiles to composite them.
+    ids: Arc<[u16]>,
+    cover_settings: CoverSettings,
+    /// What the covered tiles show.
+    cover: RgbaImage,
+    covered: Vec<bool>,
+    /// Tiles whose cover was toggled, oldest first.
+    history: Vec<usize>,
+    /// Toggles taken back by undo, most recently undone last.
+    undone: Vec<usize>,
+    /// Tile toggled last, whatever did it.
+    last_toggled: Option<usize>,
+    /// The frame drawn on the CPU, `None` while it is composited on the GPU out of `ids` and
+    /// `covered`. Reveals are a flag flip then and the cover stands in for the frame.
+    frame: Option<RgbaImage>,
+}
+
+impl Tiles {
+    pub fn new(
+        settings: TileSettings,
+        order: Vec<(u8, u8)>,
+        reveal_order: RevealOrder,
+        detail_bias: f32,
+        cover_settings: CoverSettings,
+        seed: u64,
+    ) -> Self {
+        Self {
+            settings,
+            order,
+            reveal_order,
+            queue: vec![],
+            cursor: 0,
+            detail_bias,
+            weights: vec![],
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            tile_data: TileData { tiles: vec![] },
+            index: HashMap::new(),
+            cover_settings,
+            cover: RgbaImage::new(0, 0),
+            covered: vec![],
+            history: vec![],
+            undone: vec![],
+            last_toggled: None,
+            ids: Arc::from([]),
+            frame: None,
+        }
+    }
+
+    /// Reveals a covered tile or covers a revealed one again.
+    fn toggle(&mut self, original: &DynamicImage, idx: usize) -> FrameChange {
+        let tile = &self.tile_data.tiles[idx];
+        if let Some(frame) = &mut self.frame {
+            if self.covered[idx] {
+                reveal_tile(frame, original, tile);
+            } else {
+                cover_tile(frame, &self.cover, tile);
+            }
+        }
+        if !self.covered[idx] {
+            self.cursor = 0;
+        }
+        self.covered[idx] = !self.covered[idx];
+        self.last_toggled = Some(idx);
+
+        let Tile {
+            x,
+            y,
+            width,
+            height,
+            ..
+        } = *tile;
+        FrameChange::Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Queues the scripted order followed by the reveal order, from the start.
+    fn fill_queue(&mut self) {
+        self.queue = self.order.clone();
+        self.queue
+            .extend(order_tiles(&self.tile_data.tiles, self.reveal_order));
+        self.cursor = 0;
+    }
+
+    /// Toggles the tile as a new action, which cannot be redone past.
+    fn toggle_new(&mut self, original: &DynamicImage, idx: usize) -> FrameChange {
+        self.history.push(idx);
+        self.undone.clear();
+        self.toggle(original, idx)
+    }
+}
+
+impl RevealStrategy for Tiles {
+    fn reset(&mut self, original: &DynamicImage) {
+        let (width, height) = (original.width(), original.height());
+        self.tile_data = gen_tiles(width, height, &self.settings, self.seed);
+        self.covered = vec![true; self.tile_data.tiles.len()];
+        self.index = self
+            .tile_data
+            .tiles
+            .iter()
+            .enumerate()
+            .map(|(idx, tile)| ((tile.row, tile.column), idx))
+            .collect();
+        self.history.clear();
+        self.undone.clear();
+        self.last_toggled = None;
+        self.weights = if self.detail_bias > 0.0 && self.reveal_order == RevealOrder::Random {
+            tile_weights(original, &self.tile_data.tiles, self.detail_bias)
+        } else {
+            vec![]
+        };
+        self.fill_queue();
+        self.rng = StdRng::seed_from_u64(self.seed);
+
+        self.cover = create_cover_image(original, &self.cover_settings, self.seed);
+        if self.cover_settings.style == CoverStyle::Numbers {
+            let color = contrasting_color(self.cover_settings.color);
+            number_tiles(&mut self.cover, &self.tile_data.tiles, color);
+        }
+        // Jigsaw pieces are outlined so the audience can see them.
+        if self.settings.tiling == Tiling::Jigsaw {
+            outline_tiles(&mut self.cover, &self.tile_data.tiles, COVER_OUTLINE_COLOR);
+        }
+        let tiles = &self.tile_data.tiles;
+        if tiles.len() <= MAX_TILES {
+            self.ids = tile_ids(width, height, tiles).into();
+            self.frame = None;
+        } else {
+            self.ids = Arc::from([]);
+            self.frame = Some(self.cover.clone());
+        }
+    }
+
+    fn step(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        // Entries of tiles that are already revealed, or that no longer exist after the grid
+        // changed, are skipped.
+        while let Some(position) = self.queue.get(self.cursor) {
+            match self.index.get(position) {
+                Some(&idx) if self.covered[idx] => return Some(self.toggle_new(original, idx)),
+                _ => self.cursor += 1,
+            }
+        }
+
+        let covered: Vec<usize> = (0..self.covered.len())
+            .filter(|&idx| self.covered[idx])
+            .collect();
+        if covered.is_empty() {
+            return None;
+        }
+
+        let weights = if self.weights.is_empty() {
+            None
+        } else {
+            WeightedIndex::new(covered.iter().map(|&idx| self.weights[idx])).ok()
+        };
+        let pick = match weights {
+            Some(weights) => weights.sample(&mut self.rng),
+            None => self.rng.gen_range(0..=covered.len() - 1),
+        };
+        Some(self.toggle_new(original, covered[pick]))
+    }
+
+    /// Reveals the tile at the given pixel, or covers it again if it is already revealed.
+    fn reveal_at(&mut self, original: &DynamicImage, x: u32, y: u32) -> Option<FrameChange> {
+        let idx = self
+            .tile_data
+            .tiles
+            .iter()
+            .position(|tile| tile.contains(x, y))?;
+        Some(self.toggle_new(original, idx))
+    }
+
+    /// Reveals the tile at the given grid position, or covers it again if it is already revealed.
+    fn reveal_tile_at(
+        &mut self,
+        original: &DynamicImage,
+        row: u8,
+        column: u8,
+    ) -> Option<FrameChange> {
+        let idx = *self.index.get(&(row, column))?;
+        Some(self.toggle_new(original, idx))
+    }
+
+    /// Composited frames take the new original straight from the caller, only a frame drawn
+    /// here has its revealed tiles drawn again.
+    fn original_changed(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        let frame = self.frame.as_mut()?;
+        let revealed = self.tile_data.tiles.iter().zip(&self.covered);
+        for (tile, _) in revealed.filter(|(_, &covered)| !covered) {
+            reveal_tile(frame, original, tile);
+        }
+        Some(FrameChange::Full)
+    }
+
+    /// Tiles revealed so far stay revealed, the next steps follow the new order.
+    fn set_tile_order(&mut self, order: Vec<(u8, u8)>) {
+        self.order = order;
+        self.fill_queue();
+    }
+
+    /// Reveals the queued tiles in order and then the rest, in one pass over the queue.
+    /// Every tile is its own entry in the history, as if it was stepped to.
+    fn reveal_all(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        let queued = self.queue[self.cursor..]
+            .iter()
+            .filter_map(|position| self.index.get(position).copied());
+        let order: Vec<usize> = queued.chain(0..self.covered.len()).collect();
+        self.cursor = self.queue.len();
+
+        let mut change = None;
+        for idx in order {
+            if self.covered[idx] {
+                self.toggle_new(original, idx);
+                change = Some(FrameChange::Full);
+            }
+        }
+        change
+    }
+
+    fn undo(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        let idx = self.history.pop()?;
+        self.undone.push(idx);
+        Some(self.toggle(original, idx))
+    }
+
+    fn redo(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        let idx = self.undone.pop()?;
+        self.history.push(idx);
+        Some(self.toggle(original, idx))
+    }
+
+    fn is_complete(&self) -> bool {
+        !self.covered.contains(&true)
+    }
+
+    fn frame(&self) -> &RgbaImage {
+        self.frame.as_ref().unwrap_or(&self.cover)
+    }
+
+    fn tile_map(&self) -> Option<TileMap> {
+        if self.frame.is_some() {
+            return None;
+        }
+
+        let (width, height) = self.cover.dimensions();
+        Some(TileMap {
+            width,
+            height,
+            ids: self.ids.clone(),
+        })
+    }
+
+    fn cover(&self) -> Option<&RgbaImage> {
+        Some(&self.cover)
+    }
+
+    fn covered_tiles(&self) -> Option<&[bool]> {
+        Some(&self.covered)
+    }
+
+    fn tiles(&self) -> Option<&[Tile]> {
+        Some(&self.tile_data.tiles)
+    }
+
+    /// The cover of the tile if it was just revealed, the image if it was covered again.
+    fn replaced(&self, original: &DynamicImage) -> Option<RgbaImage> {
+        let idx = self.last_toggled?;
+        let tile = &self.tile_data.tiles[idx];
+        let revealed = !self.covered[idx];
+        Some(RgbaImage::from_fn(tile.width, tile.height, |x, y| {
+            let (x, y) = (tile.x + x, tile.y + y);
+            if !tile.contains(x, y) {
+                Rgba([0, 0, 0, 0])
+            } else if revealed {
+                *self.cover.get_pixel(x, y)
+            } else {
+                original.get_pixel(x, y)
+            }
+        }))
+    }
+
+    fn host_image(&self, original: &DynamicImage) -> DynamicImage {
+        let mut image = original.to_rgba8();
+        outline_tiles(&mut image, &self.tile_data.tiles, OUTLINE_COLOR);
+        // The host sees the same numbers the audience calls out.
+        if self.cover_settings.style == CoverStyle::Numbers {
+            number_tiles(&mut image, &self.tile_data.tiles, OUTLINE_COLOR);
+        }
+        DynamicImage::ImageRgba8(image)
+    }
+
+    fn host_view(&self) -> HostView {
+        HostView::Tiles {
+            settings: self.settings,
+            // Only the random tilings are cut by the seed.
+            seed: match self.settings.tiling {
+                Tiling::Voronoi | Tiling::Jigsaw => self.seed,
+                Tiling::Grid | Tiling::Hexagons => 0,
+            },
+            numbered: self.cover_settings.style == CoverStyle::Numbers,
+        }
+    }
+}
+
+/// Prints the number of every tile, counting from 1, in the middle of it.
+fn number_tiles(image: &mut RgbaImage, tiles: &[Tile], color: Rgba<u8>) {
+    for (idx, tile) in tiles.iter().enumerate() {
+        let center = (tile.x + tile.width / 2, tile.y + tile.height / 2);
+        let height = (tile.width.min(tile.height) as f32 * NUMBER_SIZE) as u32;
+        draw_number(image, idx + 1, center, height, color);
+    }
+}
+
+/// Weighs the tiles by how little detail they have. Ranks are used instead of raw detail so the
+/// bias behaves the same on busy and on plain images.
+fn tile_weights(original: &DynamicImage, tiles: &[Tile], bias: f32) -> Vec<f64> {
+    let luma = original.to_luma8();
+    let details: Vec<f32> = tiles.iter().map(|tile| tile_detail(&luma, tile)).collect();
+
+    let mut by_detail: Vec<usize> = (0..tiles.len()).collect();
+    by_detail.sort_by(|&a, &b| details[a].total_cmp(&details[b]));
+
+    let mut weights = vec![0.0; tiles.len()];
+    let last = tiles.len().saturating_sub(1).max(1) as f64;
+    for (rank, idx) in by_detail.into_iter().enumerate() {
+        weights[idx] = (-(bias as f64) * DETAIL_BIAS_SCALE * rank as f64 / last).exp();
+    }
+    weights
+}
+
+/// Parses a scripted order saved as one `row,column` pair per line.
+pub fn parse_tile_order(text: &str) -> Option<Vec<(u8, u8)>> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (row, column) = line.split_once(',')?;
+            Some((row.trim().parse().ok()?, column.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+pub fn format_tile_order(order: &[(u8, u8)]) -> String {
+    order
+        .iter()
+        .map(|(row, column)| format!("{row},{column}\n"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiles(order: Vec<(u8, u8)>, reveal_order: RevealOrder, bias: f32, seed: u64) -> Tiles {
+        let settings = TileSettings {
+            rows: 3,
+            columns: 4,
+            tiling: Tiling::Grid,
+            voronoi_cells: 10,
+        };
+        let cover = CoverSettings {
+            style: CoverStyle::Solid,
+            color: [0, 0, 0],
+            image: None,
+            tile_image: false,
+        };
+        Tiles::new(settings, order, reveal_order, bias, cover, seed)
+    }
+
+    fn original() -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(40, 30, |x, y| {
+            Rgba([(x * 6) as u8, (y * 8) as u8, ((x * y) % 256) as u8, 255])
+        }))
+    }
+
+    /// Steps until everything is revealed, returning the `(row, column)` revealed by each step.
+    fn reveals(tiles: &mut Tiles, original: &DynamicImage) -> Vec<(u8, u8)> {
+        let mut revealed = vec![];
+        while tiles.step(original).is_some() {
+            let tile = &tiles.tile_data.tiles[tiles.last_toggled.unwrap()];
+            revealed.push((tile.row, tile.column));
+        }
+        assert!(tiles.is_complete());
+        revealed
+    }
+
+    /// The tiles revealed by stepping through a random round played with `seed`.
+    fn random_reveals(bias: f32, seed: u64) -> Vec<(u8, u8)> {
+        let original = original();
+        let mut tiles = tiles(vec![], RevealOrder::Random, bias, seed);
+        tiles.reset(&original);
+        reveals(&mut tiles, &original)
+    }
+
+    fn assert_follows_the_seed(bias: f32) {
+        let first = random_reveals(bias, 42);
+        assert_eq!(first, random_reveals(bias, 42));
+        assert_ne!(first, random_reveals(bias, 43));
+
+        let mut cells = first.clone();
+        cells.sort();
+        cells.dedup();
+        assert_eq!(cells.len(), 12);
+    }
+
+    #[test]
+    fn random_steps_follow_the_seed() {
+        assert_follows_the_seed(0.0);
+    }
+
+    #[test]
+    fn reset_starts_the_sequence_over() {
+        let original = original();
+        let mut tiles = tiles(vec![], RevealOrder::Random, 0.0, 5);
+        tiles.reset(&original);
+        let first = reveals(&mut tiles, &original);
+        tiles.reset(&original);
+        assert_eq!(reveals(&mut tiles, &original), first);
+    }
+
+    #[test]
+    fn scripted_order_comes_first() {
+        let original = original();
+        let order = vec![(2, 3), (9, 9), (0, 0), (2, 3)];
+        let mut tiles = tiles(order, RevealOrder::Raster, 0.0, 0);
+        tiles.reset(&original);
+
+        let revealed = reveals(&mut tiles, &original);
+        assert_eq!(revealed[..3], [(2, 3), (0, 0), (0, 1)]);
+        assert_eq!(revealed.len(), 12);
+    }
+
+    #[test]
+    fn tile_order_round_trip() {
+        let order = vec![(0, 0), (2, 11), (255, 7)];
+        assert_eq!(parse_tile_order(&format_tile_order(&order)), Some(order));
+        assert_eq!(
+            parse_tile_order("1, 2\n\n 3 ,4\n"),
+            Some(vec![(1, 2), (3, 4)])
+        );
+        assert_eq!(parse_tile_order(""), Some(vec![]));
+        for text in ["1;2", "1,2,3", "256,0", "a,1", "1,"] {
+            assert_eq!(parse_tile_order(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn weighted_steps_follow_the_seed() {
+        assert_follows_the_seed(0.8);
+    }
+
+    #[test]
+    fn undone_tiles_keep_their_place() {
+        let original = original();
+        let mut tiles = tiles(vec![], RevealOrder::Raster, 0.0, 0);
+        tiles.reset(&original);
+
+        tiles.step(&original);
+        tiles.step(&original);
+        tiles.undo(&original);
+        assert_eq!(tiles.covered[..3], [false, true, true]);
+        tiles.redo(&original);
+        assert_eq!(tiles.covered[..3], [false, false, true]);
+        tiles.undo(&original);
+        tiles.undo(&original);
+        assert_eq!(reveals(&mut tiles, &original)[..2], [(0, 0), (0, 1)]);
+    }
+}
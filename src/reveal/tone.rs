@@ -0,0 +1,90 @@
+use image::{DynamicImage, Luma, RgbaImage};
+
+use super::{FrameChange, RevealStrategy};
+
+/// Frames of the tone reveal: posterized grey levels (256 is plain greyscale),
+/// followed by how much colour is mixed back in.
+const TONE_STAGES: [ToneStage; 8] = [
+    ToneStage::Grey(2),
+    ToneStage::Grey(3),
+    ToneStage::Grey(4),
+    ToneStage::Grey(8),
+    ToneStage::Grey(256),
+    ToneStage::Color(0.33),
+    ToneStage::Color(0.66),
+    ToneStage::Color(1.0),
+];
+
+#[derive(Clone, Copy)]
+enum ToneStage {
+    Grey(u32),
+    Color(f32),
+}
+
+/// Starts from a black and white silhouette and adds grey levels, then colour, every step.
+#[derive(Default)]
+pub struct Tone {
+    step: usize,
+    frame: RgbaImage,
+}
+
+impl RevealStrategy for Tone {
+    fn reset(&mut self, original: &DynamicImage) {
+        self.step = 0;
+        self.frame = tone_frame(original, self.step).to_rgba8();
+    }
+
+    fn step(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        if self.is_complete() {
+            return None;
+        }
+
+        self.step += 1;
+        self.frame = tone_frame(original, self.step).to_rgba8();
+        Some(FrameChange::Full)
+    }
+
+    fn original_changed(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        self.frame = tone_frame(original, self.step).to_rgba8();
+        Some(FrameChange::Full)
+    }
+
+    fn is_complete(&self) -> bool {
+        self.step == TONE_STAGES.len() - 1
+    }
+
+    fn frame(&self) -> &RgbaImage {
+        &self.frame
+    }
+}
+
+/// Frame `step` of the tone reveal, from a black and white silhouette through more and more
+/// grey levels to the original colours.
+fn tone_frame(original: &DynamicImage, step: usize) -> DynamicImage {
+    let stage = TONE_STAGES[step.min(TONE_STAGES.len() - 1)];
+    let mut frame = original.to_rgba8();
+    let grey = original.to_luma8();
+    // The silhouette splits at the average brightness so it stays readable on dark or light shots.
+    let mean = grey.pixels().map(|p| p.0[0] as u64).sum::<u64>() / grey.len().max(1) as u64;
+
+    for (pixel, Luma([luma])) in frame.pixels_mut().zip(grey.pixels()) {
+        let luma = *luma as f32;
+        match stage {
+            ToneStage::Grey(2) => {
+                let value = if luma as u64 >= mean { 255 } else { 0 };
+                pixel.0[..3].fill(value);
+            }
+            ToneStage::Grey(levels) => {
+                let step = 255.0 / (levels - 1) as f32;
+                let value = ((luma / step).round() * step) as u8;
+                pixel.0[..3].fill(value);
+            }
+            ToneStage::Color(amount) => {
+                for c in pixel.0.iter_mut().take(3) {
+                    *c = (luma + (*c as f32 - luma) * amount).round() as u8;
+                }
+            }
+        }
+    }
+    DynamicImage::ImageRgba8(frame)
+}
@@ -0,0 +1,144 @@
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImage, RgbaImage};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{FrameChange, RevealStrategy};
+use crate::my_image::create_black_image;
+
+/// Starts zoomed into one point of the image and zooms out with every step.
+pub struct Zoom {
+    /// Magnification of the first frame.
+    start: f32,
+    /// How much each step zooms out.
+    factor: f32,
+    /// Point to zoom into relative to the image size, `None` picks a seeded random one
+    /// on reset.
+    chosen_focus: Option<(f32, f32)>,
+    /// Point to zoom into in pixels.
+    focus: (f32, f32),
+    /// Aspect ratio of the display the frames are shown on.
+    aspect: f32,
+    /// Seeds the random focus.
+    seed: u64,
+    step: usize,
+    frame: RgbaImage,
+}
+
+impl Zoom {
+    pub fn new(
+        start: f32,
+        factor: f32,
+        chosen_focus: Option<(f32, f32)>,
+        aspect: f32,
+        seed: u64,
+    ) -> Self {
+        Self {
+            start,
+            factor,
+            chosen_focus,
+            focus: (0.0, 0.0),
+            aspect,
+            seed,
+            step: 0,
+            frame: RgbaImage::new(0, 0),
+        }
+    }
+
+    /// Magnification of the current step, never below the whole image.
+    fn zoom(&self) -> f32 {
+        (self.start / self.factor.powi(self.step as i32)).max(1.0)
+    }
+
+    fn render(&mut self, original: &DynamicImage) {
+        self.frame = zoom_frame(original, self.zoom(), self.focus, self.aspect).to_rgba8();
+    }
+}
+
+impl RevealStrategy for Zoom {
+    fn reset(&mut self, original: &DynamicImage) {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let (x, y) = self.chosen_focus.unwrap_or_else(|| rng.gen());
+        self.focus = (x * original.width() as f32, y * original.height() as f32);
+        self.step = 0;
+        self.render(original);
+    }
+
+    fn step(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        if self.is_complete() {
+            return None;
+        }
+
+        self.step += 1;
+        self.render(original);
+        Some(FrameChange::Full)
+    }
+
+    fn display_resized(&mut self, original: &DynamicImage, aspect: f32) -> Option<FrameChange> {
+        self.aspect = aspect;
+        self.render(original);
+        Some(FrameChange::Full)
+    }
+
+    fn original_changed(&mut self, original: &DynamicImage) -> Option<FrameChange> {
+        self.render(original);
+        Some(FrameChange::Full)
+    }
+
+    fn is_complete(&self) -> bool {
+        self.zoom() == 1.0
+    }
+
+    fn frame(&self) -> &RgbaImage {
+        &self.frame
+    }
+}
+
+/// Frame of the zoom reveal at magnification `zoom` around `focus`, given in pixels.
+/// The visible region always has the `aspect` of the display, so at magnification 1 the whole
+/// image is shown centred with black bars where it does not fill the display.
+fn zoom_frame(original: &DynamicImage, zoom: f32, focus: (f32, f32), aspect: f32) -> DynamicImage {
+    let (width, height) = (original.width() as f32, original.height() as f32);
+    let (full_width, full_height) = if aspect > width / height {
+        (height * aspect, height)
+    } else {
+        (width, width / aspect)
+    };
+    let (full_x, full_y) = ((width - full_width) / 2.0, (height - full_height) / 2.0);
+
+    let (crop_width, crop_height) = (full_width / zoom, full_height / zoom);
+    let crop_x = (focus.0 - crop_width / 2.0).clamp(full_x, full_x + full_width - crop_width);
+    let crop_y = (focus.1 - crop_height / 2.0).clamp(full_y, full_y + full_height - crop_height);
+
+    // Only the part of the crop overlapping the image is scaled, the rest stays black.
+    let (src_x, src_y) = (crop_x.max(0.0), crop_y.max(0.0));
+    let src_width = (crop_x + crop_width).min(width) - src_x;
+    let src_height = (crop_y + crop_height).min(height) - src_y;
+    let (scale_x, scale_y) = (width / crop_width, height / crop_height);
+
+    let mut frame = DynamicImage::ImageRgba8(create_black_image(width as u32, height as u32));
+    let part = original
+        .crop_imm(
+            src_x as u32,
+            src_y as u32,
+            (src_width as u32).max(1),
+            (src_height as u32).max(1),
+        )
+        .resize_exact(
+            ((src_width * scale_x).round() as u32).clamp(1, width as u32),
+            ((src_height * scale_y).round() as u32).clamp(1, height as u32),
+            FilterType::Triangle,
+        );
+    let dest_x = ((src_x - crop_x) * scale_x).round() as u32;
+    let dest_y = ((src_y - crop_y) * scale_y).round() as u32;
+    let part_width = part.width().min(width as u32 - dest_x.min(width as u32));
+    let part_height = part.height().min(height as u32 - dest_y.min(height as u32));
+    frame
+        .copy_from(
+            &part.crop_imm(0, 0, part_width, part_height),
+            dest_x,
+            dest_y,
+        )
+        .expect("Part is clamped to the frame.");
+    frame
+}
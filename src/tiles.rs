@@ -0,0 +1,813 @@
+use image::{DynamicImage, GenericImageView, GrayImage, Rgba, RgbaImage};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Knob radius of jigsaw pieces relative to the shorter side of a grid cell.
+const KNOB_RADIUS: f32 = 0.2;
+/// How far past the edge the knob centre sits, relative to the knob radius.
+const KNOB_OFFSET: f32 = 0.6;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Tiling {
+    Grid,
+    Hexagons,
+    Voronoi,
+    Jigsaw,
+}
+
+impl Tiling {
+    pub const ALL: [Tiling; 4] = [
+        Tiling::Grid,
+        Tiling::Hexagons,
+        Tiling::Voronoi,
+        Tiling::Jigsaw,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tiling::Grid => "Grid",
+            Tiling::Hexagons => "Hexagons",
+            Tiling::Voronoi => "Voronoi",
+            Tiling::Jigsaw => "Jigsaw",
+        }
+    }
+}
+
+/// Order in which tiles are revealed once the scripted order runs out.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum RevealOrder {
+    Random,
+    SpiralIn,
+    SpiralOut,
+    Raster,
+    Snake,
+    Checkerboard,
+    CenterOut,
+    EdgesIn,
+}
+
+impl RevealOrder {
+    pub const ALL: [RevealOrder; 8] = [
+        RevealOrder::Random,
+        RevealOrder::SpiralIn,
+        RevealOrder::SpiralOut,
+        RevealOrder::Raster,
+        RevealOrder::Snake,
+        RevealOrder::Checkerboard,
+        RevealOrder::CenterOut,
+        RevealOrder::EdgesIn,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RevealOrder::Random => "Random",
+            RevealOrder::SpiralIn => "Spiral inwards",
+            RevealOrder::SpiralOut => "Spiral outwards",
+            RevealOrder::Raster => "Row by row",
+            RevealOrder::Snake => "Snake",
+            RevealOrder::Checkerboard => "Checkerboard",
+            RevealOrder::CenterOut => "Centre out",
+            RevealOrder::EdgesIn => "Edges in",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct TileSettings {
+    pub rows: u8,
+    pub columns: u8,
+    pub tiling: Tiling,
+    /// Number of cells of the Voronoi tiling, rows and columns are ignored for it.
+    pub voronoi_cells: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct Tile {
+    pub row: u8,
+    pub column: u8,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Row-major flags over the bounding box telling which pixels belong to the tile.
+    /// `None` means the tile is the whole bounding box.
+    pub mask: Option<Vec<bool>>,
+}
+
+impl Tile {
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        if x < self.x || y < self.y || x >= self.x + self.width || y >= self.y + self.height {
+            return false;
+        }
+
+        match &self.mask {
+            Some(mask) => mask[((y - self.y) * self.width + (x - self.x)) as usize],
+            None => true,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TileData {
+    pub tiles: Vec<Tile>,
+}
+
+/// Cuts a `width`x`height` image into tiles. `seed` drives the shapes of the random tilings.
+pub fn gen_tiles(width: u32, height: u32, settings: &TileSettings, seed: u64) -> TileData {
+    match settings.tiling {
+        Tiling::Grid => gen_grid_tiles(width, height, settings.rows, settings.columns),
+        Tiling::Hexagons => gen_hex_tiles(width, height, settings.rows, settings.columns),
+        Tiling::Voronoi => gen_voronoi_tiles(width, height, settings.voronoi_cells, seed),
+        Tiling::Jigsaw => gen_jigsaw_tiles(width, height, settings.rows, settings.columns, seed),
+    }
+}
+
+/// Lists the tiles as `(row, column)` in the given order, or nothing for the random order.
+/// Orders go by grid position, so shaped tilings follow their rows and columns.
+pub fn order_tiles(tiles: &[Tile], order: RevealOrder) -> Vec<(u8, u8)> {
+    let rows = tiles
+        .iter()
+        .map(|tile| tile.row as i32 + 1)
+        .max()
+        .unwrap_or(0);
+    let columns = tiles
+        .iter()
+        .map(|tile| tile.column as i32 + 1)
+        .max()
+        .unwrap_or(0);
+    let (center_row, center_column) = ((rows - 1) as f32 / 2.0, (columns - 1) as f32 / 2.0);
+
+    // How many steps in from the border the tile is.
+    let ring = |row: i32, column: i32| {
+        row.min(column)
+            .min(rows - 1 - row)
+            .min(columns - 1 - column)
+    };
+    // Place along the ring of the tile, clockwise from the top left tile of the ring.
+    let along_ring = |row: i32, column: i32| {
+        let ring = ring(row, column);
+        let (top, left, bottom, right) = (ring, ring, rows - 1 - ring, columns - 1 - ring);
+        let (width, height) = (right - left, bottom - top);
+        if row == top {
+            column - left
+        } else if column == right {
+            width + row - top
+        } else if row == bottom {
+            width + height + right - column
+        } else {
+            2 * width + height + bottom - row
+        }
+    };
+    let distance =
+        |row: i32, column: i32| (row as f32 - center_row).hypot(column as f32 - center_column);
+
+    let mut cells: Vec<(i32, i32)> = tiles
+        .iter()
+        .map(|tile| (tile.row as i32, tile.column as i32))
+        .collect();
+    cells.sort();
+    match order {
+        RevealOrder::Random => return vec![],
+        RevealOrder::Raster => {}
+        RevealOrder::SpiralIn | RevealOrder::SpiralOut => {
+            cells.sort_by(|&(r1, c1), &(r2, c2)| {
+                ring(r1, c1)
+                    .cmp(&ring(r2, c2))
+                    .then(along_ring(r1, c1).cmp(&along_ring(r2, c2)))
+            });
+            if order == RevealOrder::SpiralOut {
+                cells.reverse();
+            }
+        }
+        RevealOrder::Snake => {
+            cells.sort_by_key(|&(row, column)| (row, if row % 2 == 0 { column } else { -column }))
+        }
+        RevealOrder::Checkerboard => cells.sort_by_key(|&(row, column)| (row + column) % 2),
+        RevealOrder::CenterOut => {
+            cells.sort_by(|&(r1, c1), &(r2, c2)| distance(r1, c1).total_cmp(&distance(r2, c2)))
+        }
+        RevealOrder::EdgesIn => cells.sort_by_key(|&(row, column)| ring(row, column)),
+    }
+
+    cells
+        .into_iter()
+        .map(|(row, column)| (row as u8, column as u8))
+        .collect()
+}
+
+/// Name the audience uses for a tile, its column as letters and its row as a number counting
+/// from 1, like a spreadsheet cell: A1, B3, AA12.
+pub fn tile_label(row: u8, column: u8) -> String {
+    let mut letters = vec![];
+    let mut column = column as u32 + 1;
+    while column > 0 {
+        column -= 1;
+        letters.push((b'A' + (column % 26) as u8) as char);
+        column /= 26;
+    }
+    let letters: String = letters.iter().rev().collect();
+    format!("{letters}{}", row as u32 + 1)
+}
+
+/// Reads a label written by `tile_label` back into `(row, column)`, ignoring case and spaces.
+pub fn parse_tile_label(label: &str) -> Option<(u8, u8)> {
+    let label: String = label
+        .split_whitespace()
+        .collect::<String>()
+        .to_ascii_uppercase();
+    let split = label.find(|c: char| !c.is_ascii_uppercase())?;
+    let (letters, number) = label.split_at(split);
+    if letters.is_empty() {
+        return None;
+    }
+
+    let column = letters.bytes().try_fold(0u32, |column, c| {
+        Some(column * 26 + (c - b'A') as u32 + 1).filter(|&column| column <= 256)
+    })?;
+    let row: u8 = number.parse().ok().filter(|&row| row > 0)?;
+    Some((row - 1, (column - 1) as u8))
+}
+
+/// Finds the tile the host typed, a label written by `tile_label` or the number the Numbers
+/// cover prints on it, counting from 1 in the order of `tiles`. Returns `(row, column)`.
+pub fn find_tile(name: &str, tiles: &[Tile]) -> Option<(u8, u8)> {
+    match name.trim().parse::<usize>() {
+        Ok(number) => {
+            let tile = tiles.get(number.checked_sub(1)?)?;
+            Some((tile.row, tile.column))
+        }
+        Err(_) => parse_tile_label(name),
+    }
+}
+
+/// Returns the start and length of the `idx`-th of `count` spans covering `len` pixels.
+/// The last span absorbs the remainder so that the spans always add up to `len`.
+pub fn tile_span(len: u32, count: u8, idx: u8) -> (u32, u32) {
+    let span = len / count as u32;
+    let start = span * idx as u32;
+    if idx + 1 == count {
+        (start, len - start)
+    } else {
+        (start, span)
+    }
+}
+
+fn gen_grid_tiles(width: u32, height: u32, rows: u8, columns: u8) -> TileData {
+    let mut tiles = vec![];
+    for x in 0..rows {
+        let (pos_y, tile_height) = tile_span(height, rows, x);
+        for y in 0..columns {
+            let (pos_x, tile_width) = tile_span(width, columns, y);
+            // Grids finer than the image leave some spans empty, there is nothing to reveal there.
+            if tile_width == 0 || tile_height == 0 {
+                continue;
+            }
+
+            tiles.push(Tile {
+                row: x,
+                column: y,
+                x: pos_x,
+                y: pos_y,
+                width: tile_width,
+                height: tile_height,
+                mask: None,
+            });
+        }
+    }
+
+    TileData { tiles }
+}
+
+/// Lays out pointy-top hexagons with every odd row shifted by half a hexagon.
+/// Each pixel goes to the nearest hexagon centre, so the cells cover the image exactly.
+fn gen_hex_tiles(width: u32, height: u32, rows: u8, columns: u8) -> TileData {
+    let (rows, columns) = (rows as i32, columns as i32);
+    let dx = width as f32 / columns as f32;
+    let dy = height as f32 / rows as f32;
+    // Nearest-centre regions of the lattice are regular hexagons once rows are
+    // `sqrt(3) / 2` columns apart, so distances are measured with y scaled to that.
+    let y_scale = dx * 3f32.sqrt() / 2.0 / dy;
+    let offset = |row: i32| if row % 2 == 1 { 0.5 } else { 0.0 };
+
+    let mut owners = Vec::with_capacity((width * height) as usize);
+    for py in 0..height {
+        let fy = py as f32 + 0.5;
+        let center_row = (fy / dy) as i32;
+        for px in 0..width {
+            let fx = px as f32 + 0.5;
+            let mut nearest = (f32::MAX, 0);
+            for row in (center_row - 1).max(0)..=(center_row + 1).min(rows - 1) {
+                let center_column = (fx / dx - 0.5 - offset(row)).round() as i32;
+                for column in (center_column - 1).max(0)..=(center_column + 1).min(columns - 1) {
+                    let cx = (column as f32 + 0.5 + offset(row)) * dx;
+                    let cy = (row as f32 + 0.5) * dy;
+                    let distance = (fx - cx).powi(2) + ((fy - cy) * y_scale).powi(2);
+                    if distance < nearest.0 {
+                        nearest = (distance, row * columns + column);
+                    }
+                }
+            }
+            owners.push(nearest.1 as usize);
+        }
+    }
+
+    let cells: Vec<(u8, u8)> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (row as u8, column as u8)))
+        .collect();
+
+    TileData {
+        tiles: tiles_from_owners(width, height, &owners, &cells),
+    }
+}
+
+/// Scatters `cells` random sites over the image and gives every pixel to the nearest one.
+/// Sites are numbered in reading order, top to bottom in bands and left to right within a band.
+fn gen_voronoi_tiles(width: u32, height: u32, cells: u16, seed: u64) -> TileData {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut sites: Vec<(f32, f32)> = (0..cells)
+        .map(|_| {
+            (
+                rng.gen_range(0.0..width as f32),
+                rng.gen_range(0.0..height as f32),
+            )
+        })
+        .collect();
+
+    let band_len = (cells as f32).sqrt().ceil() as usize;
+    sites.sort_by(|a, b| a.1.total_cmp(&b.1));
+    sites
+        .chunks_mut(band_len)
+        .for_each(|band| band.sort_by(|a, b| a.0.total_cmp(&b.0)));
+    let cells: Vec<(u8, u8)> = (0..sites.len())
+        .map(|i| ((i / band_len) as u8, (i % band_len) as u8))
+        .collect();
+
+    // Sites are bucketed so each pixel only looks at buckets around it, widening the search
+    // ring by ring until no unvisited bucket can hold anything closer.
+    let bucket_size = ((width as f32 * height as f32) / sites.len() as f32)
+        .sqrt()
+        .max(1.0);
+    let buckets_x = (width as f32 / bucket_size).ceil() as i32;
+    let buckets_y = (height as f32 / bucket_size).ceil() as i32;
+    let mut buckets = vec![vec![]; (buckets_x * buckets_y) as usize];
+    for (i, &(x, y)) in sites.iter().enumerate() {
+        let bx = ((x / bucket_size) as i32).min(buckets_x - 1);
+        let by = ((y / bucket_size) as i32).min(buckets_y - 1);
+        buckets[(by * buckets_x + bx) as usize].push(i);
+    }
+
+    let mut owners = Vec::with_capacity((width * height) as usize);
+    for py in 0..height {
+        let fy = py as f32 + 0.5;
+        let by = (fy / bucket_size) as i32;
+        for px in 0..width {
+            let fx = px as f32 + 0.5;
+            let bx = (fx / bucket_size) as i32;
+            let mut nearest = (f32::MAX, 0);
+            for ring in 0..buckets_x.max(buckets_y) {
+                for y in (by - ring).max(0)..=(by + ring).min(buckets_y - 1) {
+                    for x in (bx - ring).max(0)..=(bx + ring).min(buckets_x - 1) {
+                        if (x - bx).abs() != ring && (y - by).abs() != ring {
+                            continue;
+                        }
+
+                        for &i in &buckets[(y * buckets_x + x) as usize] {
+                            let (sx, sy) = sites[i];
+                            let distance = (fx - sx).powi(2) + (fy - sy).powi(2);
+                            if distance < nearest.0 {
+                                nearest = (distance, i);
+                            }
+                        }
+                    }
+                }
+
+                if nearest.0 <= (ring as f32 * bucket_size).powi(2) {
+                    break;
+                }
+            }
+            owners.push(nearest.1);
+        }
+    }
+
+    TileData {
+        tiles: tiles_from_owners(width, height, &owners, &cells),
+    }
+}
+
+/// Starts from the regular grid and grows a round knob across every inner edge,
+/// each one pointing into a randomly chosen neighbour.
+fn gen_jigsaw_tiles(width: u32, height: u32, rows: u8, columns: u8, seed: u64) -> TileData {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let owner = |row: u8, column: u8| row as usize * columns as usize + column as usize;
+
+    let mut owners = Vec::with_capacity((width * height) as usize);
+    for row in 0..rows {
+        let (_, tile_height) = tile_span(height, rows, row);
+        for _ in 0..tile_height {
+            for column in 0..columns {
+                let (_, tile_width) = tile_span(width, columns, column);
+                owners.extend(std::iter::repeat_n(owner(row, column), tile_width as usize));
+            }
+        }
+    }
+
+    for row in 0..rows {
+        let (pos_y, tile_height) = tile_span(height, rows, row);
+        for column in 0..columns {
+            let (pos_x, tile_width) = tile_span(width, columns, column);
+            let r = KNOB_RADIUS * tile_width.min(tile_height) as f32;
+
+            if column + 1 < columns {
+                let edge = (pos_x + tile_width) as f32;
+                let cy = pos_y as f32 + tile_height as f32 / 2.0;
+                let (owner, cx) = if rng.gen_bool(0.5) {
+                    (owner(row, column), edge + KNOB_OFFSET * r)
+                } else {
+                    (owner(row, column + 1), edge - KNOB_OFFSET * r)
+                };
+                grow_knob(&mut owners, width, height, owner, (cx, cy), r);
+            }
+
+            if row + 1 < rows {
+                let edge = (pos_y + tile_height) as f32;
+                let cx = pos_x as f32 + tile_width as f32 / 2.0;
+                let (owner, cy) = if rng.gen_bool(0.5) {
+                    (owner(row, column), edge + KNOB_OFFSET * r)
+                } else {
+                    (owner(row + 1, column), edge - KNOB_OFFSET * r)
+                };
+                grow_knob(&mut owners, width, height, owner, (cx, cy), r);
+            }
+        }
+    }
+
+    let cells: Vec<(u8, u8)> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (row, column)))
+        .collect();
+
+    TileData {
+        tiles: tiles_from_owners(width, height, &owners, &cells),
+    }
+}
+
+/// Hands a disc to `owner`. The disc centre sits just past the edge of the owner's cell,
+/// so the disc joins its piece through a narrower neck like a jigsaw knob.
+fn grow_knob(
+    owners: &mut [usize],
+    width: u32,
+    height: u32,
+    owner: usize,
+    (cx, cy): (f32, f32),
+    r: f32,
+) {
+    let (min_x, max_x) = ((cx - r).max(0.0) as u32, ((cx + r) as u32).min(width - 1));
+    let (min_y, max_y) = ((cy - r).max(0.0) as u32, ((cy + r) as u32).min(height - 1));
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let distance = (x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2);
+            if distance <= r * r {
+                owners[(y * width + x) as usize] = owner;
+            }
+        }
+    }
+}
+
+/// Builds masked tiles from a per-pixel map of indices into `cells`.
+/// Cells that did not end up owning any pixel are dropped.
+fn tiles_from_owners(width: u32, height: u32, owners: &[usize], cells: &[(u8, u8)]) -> Vec<Tile> {
+    let mut bounds = vec![(u32::MAX, u32::MAX, 0, 0); cells.len()];
+    for (i, &owner) in owners.iter().enumerate() {
+        let (x, y) = (i as u32 % width, i as u32 / width);
+        let b = &mut bounds[owner];
+        *b = (b.0.min(x), b.1.min(y), b.2.max(x), b.3.max(y));
+    }
+
+    let mut tile_idx = vec![None; cells.len()];
+    let mut tiles = vec![];
+    for (owner, (&(row, column), &(min_x, min_y, max_x, max_y))) in
+        cells.iter().zip(bounds.iter()).enumerate()
+    {
+        if min_x == u32::MAX {
+            continue;
+        }
+
+        let (tile_width, tile_height) = (max_x - min_x + 1, max_y - min_y + 1);
+        tile_idx[owner] = Some(tiles.len());
+        tiles.push(Tile {
+            row,
+            column,
+            x: min_x,
+            y: min_y,
+            width: tile_width,
+            height: tile_height,
+            mask: Some(vec![false; (tile_width * tile_height) as usize]),
+        });
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            let owner = owners[(y * width + x) as usize];
+            let tile = &mut tiles[tile_idx[owner].expect("Owner has at least this pixel.")];
+            let idx = ((y - tile.y) * tile.width + (x - tile.x)) as usize;
+            if let Some(mask) = tile.mask.as_mut() {
+                mask[idx] = true;
+            }
+        }
+    }
+
+    tiles
+}
+
+/// Copies the pixels of `tile` from `original` onto `partial`.
+pub fn reveal_tile(partial: &mut RgbaImage, original: &DynamicImage, tile: &Tile) {
+    for y in tile.y..tile.y + tile.height {
+        for x in tile.x..tile.x + tile.width {
+            if tile.contains(x, y) {
+                partial.put_pixel(x, y, original.get_pixel(x, y));
+            }
+        }
+    }
+}
+
+/// Row-major index into `tiles` of the tile every pixel belongs to, `u16::MAX` for pixels
+/// outside every tile.
+pub fn tile_ids(width: u32, height: u32, tiles: &[Tile]) -> Vec<u16> {
+    let mut ids = vec![u16::MAX; (width * height) as usize];
+    for (id, tile) in tiles.iter().enumerate() {
+        for y in tile.y..tile.y + tile.height {
+            for x in tile.x..tile.x + tile.width {
+                if tile.contains(x, y) {
+                    ids[(y * width + x) as usize] = id as u16;
+                }
+            }
+        }
+    }
+    ids
+}
+
+pub fn cover_tile(partial: &mut RgbaImage, cover: &RgbaImage, tile: &Tile) {
+    for y in tile.y..tile.y + tile.height {
+        for x in tile.x..tile.x + tile.width {
+            if tile.contains(x, y) {
+                partial.put_pixel(x, y, *cover.get_pixel(x, y));
+            }
+        }
+    }
+}
+
+/// Paints the border pixels of every masked tile, leaving plain rectangles untouched.
+pub fn outline_tiles(image: &mut RgbaImage, tiles: &[Tile], color: Rgba<u8>) {
+    for tile in tiles.iter().filter(|tile| tile.mask.is_some()) {
+        for y in tile.y..tile.y + tile.height {
+            for x in tile.x..tile.x + tile.width {
+                if !tile.contains(x, y) {
+                    continue;
+                }
+
+                let on_border = (x > 0 && !tile.contains(x - 1, y))
+                    || (y > 0 && !tile.contains(x, y - 1))
+                    || (x + 1 < image.width() && !tile.contains(x + 1, y))
+                    || (y + 1 < image.height() && !tile.contains(x, y + 1));
+                if on_border {
+                    image.put_pixel(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+/// How much detail there is in `tile`, as the mean luma gradient over its pixels.
+/// Flat sky scores close to zero while faces and text score high.
+pub fn tile_detail(luma: &GrayImage, tile: &Tile) -> f32 {
+    let (width, height) = luma.dimensions();
+    let mut total = 0u64;
+    let mut count = 0u64;
+    for y in tile.y..tile.y + tile.height {
+        for x in tile.x..tile.x + tile.width {
+            if !tile.contains(x, y) {
+                continue;
+            }
+
+            let value = luma.get_pixel(x, y).0[0] as i32;
+            let right = luma.get_pixel((x + 1).min(width - 1), y).0[0] as i32;
+            let below = luma.get_pixel(x, (y + 1).min(height - 1)).0[0] as i32;
+            total += ((right - value).abs() + (below - value).abs()) as u64;
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        0.0
+    } else {
+        total as f32 / count as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(tiling: Tiling) -> TileSettings {
+        TileSettings {
+            rows: 3,
+            columns: 4,
+            tiling,
+            voronoi_cells: 10,
+        }
+    }
+
+    fn assert_covers_every_pixel_once(settings: &TileSettings, width: u32, height: u32) {
+        let tiles = gen_tiles(width, height, settings, 7).tiles;
+        for y in 0..height {
+            for x in 0..width {
+                let owners = tiles.iter().filter(|tile| tile.contains(x, y)).count();
+                assert_eq!(owners, 1, "{settings:?} at ({x}, {y})");
+            }
+        }
+
+        let mut positions: Vec<_> = tiles.iter().map(|tile| (tile.row, tile.column)).collect();
+        positions.sort();
+        positions.dedup();
+        assert_eq!(positions.len(), tiles.len(), "{settings:?}");
+    }
+
+    #[test]
+    fn spans_add_up_to_the_length() {
+        assert_eq!(tile_span(10, 3, 0), (0, 3));
+        assert_eq!(tile_span(10, 3, 1), (3, 3));
+        assert_eq!(tile_span(10, 3, 2), (6, 4));
+        assert_eq!(tile_span(7, 1, 0), (0, 7));
+
+        for count in 1..=12 {
+            let mut end = 0;
+            for idx in 0..count {
+                let (start, len) = tile_span(101, count, idx);
+                assert_eq!(start, end);
+                end = start + len;
+            }
+            assert_eq!(end, 101);
+        }
+    }
+
+    #[test]
+    fn grid_covers_every_pixel_once() {
+        assert_covers_every_pixel_once(&settings(Tiling::Grid), 37, 23);
+        // Grids finer than the image leave out the empty spans.
+        let fine = TileSettings {
+            rows: 64,
+            columns: 64,
+            ..settings(Tiling::Grid)
+        };
+        assert_covers_every_pixel_once(&fine, 20, 10);
+    }
+
+    #[test]
+    fn hexagons_cover_every_pixel_once() {
+        assert_covers_every_pixel_once(&settings(Tiling::Hexagons), 37, 23);
+    }
+
+    /// The bounds and masks of the tiles cut with `seed`.
+    fn shapes(tiling: Tiling, seed: u64) -> Vec<(u32, u32, Option<Vec<bool>>)> {
+        gen_tiles(37, 23, &settings(tiling), seed)
+            .tiles
+            .into_iter()
+            .map(|tile| (tile.x, tile.y, tile.mask))
+            .collect()
+    }
+
+    #[test]
+    fn voronoi_covers_every_pixel_once() {
+        assert_covers_every_pixel_once(&settings(Tiling::Voronoi), 37, 23);
+    }
+
+    #[test]
+    fn voronoi_follows_the_seed() {
+        assert_eq!(shapes(Tiling::Voronoi, 3), shapes(Tiling::Voronoi, 3));
+        assert_ne!(shapes(Tiling::Voronoi, 3), shapes(Tiling::Voronoi, 4));
+    }
+
+    #[test]
+    fn jigsaw_covers_every_pixel_once() {
+        assert_covers_every_pixel_once(&settings(Tiling::Jigsaw), 37, 23);
+    }
+
+    #[test]
+    fn jigsaw_follows_the_seed() {
+        assert_eq!(shapes(Tiling::Jigsaw, 3), shapes(Tiling::Jigsaw, 3));
+        assert_ne!(shapes(Tiling::Jigsaw, 3), shapes(Tiling::Jigsaw, 4));
+    }
+
+    fn grid(rows: u8, columns: u8) -> Vec<Tile> {
+        let settings = TileSettings {
+            rows,
+            columns,
+            ..settings(Tiling::Grid)
+        };
+        gen_tiles(40, 30, &settings, 0).tiles
+    }
+
+    #[test]
+    fn orders_tiles_by_grid_position() {
+        let tiles = grid(3, 3);
+        let order = |order| order_tiles(&tiles, order);
+
+        assert!(order(RevealOrder::Random).is_empty());
+        assert_eq!(
+            order(RevealOrder::Raster),
+            [
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 1),
+                (1, 2),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+        let spiral = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+            (2, 1),
+            (2, 0),
+            (1, 0),
+            (1, 1),
+        ];
+        assert_eq!(order(RevealOrder::SpiralIn), spiral);
+        let mut spiral_out = spiral.to_vec();
+        spiral_out.reverse();
+        assert_eq!(order(RevealOrder::SpiralOut), spiral_out);
+        assert_eq!(
+            order(RevealOrder::Snake),
+            [
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (1, 1),
+                (1, 0),
+                (2, 0),
+                (2, 1),
+                (2, 2)
+            ]
+        );
+        assert_eq!(order(RevealOrder::CenterOut)[0], (1, 1));
+        assert_eq!(order(RevealOrder::EdgesIn)[8], (1, 1));
+        let checkerboard = order(RevealOrder::Checkerboard);
+        assert!(checkerboard[..5].iter().all(|(r, c)| (r + c) % 2 == 0));
+        assert!(checkerboard[5..].iter().all(|(r, c)| (r + c) % 2 == 1));
+    }
+
+    #[test]
+    fn orders_list_every_tile_once() {
+        let tiles = grid(4, 6);
+        for order in RevealOrder::ALL {
+            if order == RevealOrder::Random {
+                continue;
+            }
+
+            let mut cells = order_tiles(&tiles, order);
+            cells.sort();
+            cells.dedup();
+            assert_eq!(cells.len(), tiles.len(), "{order:?}");
+        }
+    }
+
+    #[test]
+    fn finds_tiles_by_number_or_label() {
+        let tiles = grid(2, 3);
+        assert_eq!(find_tile("1", &tiles), Some((0, 0)));
+        assert_eq!(find_tile(" 5 ", &tiles), Some((1, 1)));
+        assert_eq!(find_tile("0", &tiles), None);
+        assert_eq!(find_tile("7", &tiles), None);
+        assert_eq!(find_tile("C2", &tiles), Some((1, 2)));
+    }
+
+    #[test]
+    fn labels_round_trip() {
+        assert_eq!(tile_label(0, 0), "A1");
+        assert_eq!(tile_label(2, 25), "Z3");
+        assert_eq!(tile_label(11, 26), "AA12");
+        assert_eq!(tile_label(0, 255), "IV1");
+
+        for row in 0..255 {
+            for column in 0..=255 {
+                let label = tile_label(row, column);
+                assert_eq!(parse_tile_label(&label), Some((row, column)), "{label}");
+            }
+        }
+    }
+
+    #[test]
+    fn parses_labels_leniently() {
+        assert_eq!(parse_tile_label(" b 3 "), Some((2, 1)));
+        assert_eq!(parse_tile_label("aa12"), Some((11, 26)));
+        for label in ["", "3", "A", "A0", "1A", "A1B", "IW1", "A-1"] {
+            assert_eq!(parse_tile_label(label), None, "{label}");
+        }
+    }
+}
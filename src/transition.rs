@@ -0,0 +1,178 @@
+use std::f32::consts::FRAC_PI_2;
+
+use egui::epaint::Rgba;
+use egui::{pos2, vec2, Color32, Painter, Rect, Shape, TextureHandle};
+use image::RgbaImage;
+
+/// Number of cells along each side of a region that dissolve one by one.
+const DISSOLVE_CELLS: u32 = 12;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TransitionStyle {
+    None,
+    Fade,
+    Flip,
+    Slide,
+    Dissolve,
+}
+
+impl TransitionStyle {
+    pub const ALL: [TransitionStyle; 5] = [
+        TransitionStyle::None,
+        TransitionStyle::Fade,
+        TransitionStyle::Flip,
+        TransitionStyle::Slide,
+        TransitionStyle::Dissolve,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransitionStyle::None => "None",
+            TransitionStyle::Fade => "Fade",
+            TransitionStyle::Flip => "Flip",
+            TransitionStyle::Slide => "Slide",
+            TransitionStyle::Dissolve => "Dissolve",
+        }
+    }
+}
+
+/// The part of the frame that was there before a reveal, animated away over the new frame.
+struct Transition {
+    /// What the region looked like before, transparent where nothing changed.
+    texture: TextureHandle,
+    /// Region of the frame, relative to the frame size.
+    uv: Rect,
+    /// Time the transition was first drawn, it starts once somebody can see it.
+    start: Option<f64>,
+}
+
+/// Transitions shared between the worker, which adds one for every revealed region, and the
+/// window showing the frame, which draws them.
+pub struct Transitions {
+    pub style: TransitionStyle,
+    pub duration: f64,
+    active: Vec<Transition>,
+}
+
+impl Transitions {
+    pub fn new(style: TransitionStyle, duration: f64) -> Self {
+        Self {
+            style,
+            duration,
+            active: vec![],
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.style != TransitionStyle::None && self.duration > 0.0
+    }
+
+    /// Starts a transition of the `uv` region of the frame. `texture` holds what the region
+    /// looked like before the change, see `changed_pixels`.
+    pub fn add(&mut self, texture: TextureHandle, uv: Rect) {
+        self.active.push(Transition {
+            texture,
+            uv,
+            start: None,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    /// Draws the transitions over the frame shown in `rect` and drops the finished ones.
+    pub fn paint(&mut self, painter: &Painter, rect: Rect, time: f64) {
+        let (style, duration) = (self.style, self.duration);
+        self.active.retain_mut(|transition| {
+            let start = *transition.start.get_or_insert(time);
+            let t = ((time - start) / duration) as f32;
+            if t >= 1.0 || style == TransitionStyle::None {
+                return false;
+            }
+
+            let region = Rect::from_min_max(
+                rect.lerp(transition.uv.min.to_vec2()),
+                rect.lerp(transition.uv.max.to_vec2()),
+            );
+            paint_transition(painter, transition.texture.id(), region, style, t);
+            true
+        });
+    }
+}
+
+/// Draws the old content of `region` at progress `t` of the transition.
+fn paint_transition(
+    painter: &Painter,
+    texture: egui::TextureId,
+    region: Rect,
+    style: TransitionStyle,
+    t: f32,
+) {
+    let full_uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+    let eased = t * t * (3.0 - 2.0 * t);
+
+    match style {
+        TransitionStyle::None => {}
+        TransitionStyle::Fade => {
+            let tint = Color32::from(Rgba::from_white_alpha(1.0 - eased));
+            painter.add(Shape::image(texture, region, full_uv, tint));
+        }
+        TransitionStyle::Flip => {
+            // The old content turns edge on, showing the new content behind it.
+            let width = region.width() * (eased * FRAC_PI_2).cos();
+            let flipped = Rect::from_center_size(region.center(), vec2(width, region.height()));
+            painter.add(Shape::image(texture, flipped, full_uv, Color32::WHITE));
+        }
+        TransitionStyle::Slide => {
+            let slid = region.translate(vec2(0.0, region.height() * eased));
+            painter.with_clip_rect(region).add(Shape::image(
+                texture,
+                slid,
+                full_uv,
+                Color32::WHITE,
+            ));
+        }
+        TransitionStyle::Dissolve => {
+            let cell_size = region.size() / DISSOLVE_CELLS as f32;
+            for y in 0..DISSOLVE_CELLS {
+                for x in 0..DISSOLVE_CELLS {
+                    if dissolve_threshold(x, y) < t {
+                        continue;
+                    }
+
+                    let min = vec2(x as f32, y as f32) / DISSOLVE_CELLS as f32;
+                    let max = vec2(x as f32 + 1.0, y as f32 + 1.0) / DISSOLVE_CELLS as f32;
+                    let uv = Rect::from_min_max(min.to_pos2(), max.to_pos2());
+                    let cell = Rect::from_min_size(
+                        region.min + cell_size * vec2(x as f32, y as f32),
+                        cell_size,
+                    );
+                    painter.add(Shape::image(texture, cell, uv, Color32::WHITE));
+                }
+            }
+        }
+    }
+}
+
+/// Scrambled but stable point in `0..1` at which the cell disappears.
+fn dissolve_threshold(x: u32, y: u32) -> f32 {
+    let mut hash = x.wrapping_mul(0x9e37_79b9) ^ y.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 13;
+    (hash % 1024) as f32 / 1024.0
+}
+
+/// `before`, a region of the frame at `(x, y)`, with every pixel that is the same in `after`
+/// made transparent, so only what changed gets animated.
+pub fn changed_pixels(before: &RgbaImage, after: &RgbaImage, (x, y): (u32, u32)) -> RgbaImage {
+    RgbaImage::from_fn(before.width(), before.height(), |px, py| {
+        let old = *before.get_pixel(px, py);
+        if old == *after.get_pixel(x + px, y + py) {
+            image::Rgba([0, 0, 0, 0])
+        } else {
+            old
+        }
+    })
+}
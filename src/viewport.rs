@@ -12,6 +12,8 @@ pub trait Gui {
     fn draw(&mut self, ctx: &Context, state: EventLoopState);
 }
 
+// There is only ever one control panel, its size does not matter.
+#[allow(clippy::large_enum_variant)]
 #[enum_dispatch(Gui)]
 pub enum GuiImpl {
     ControlPanel,
@@ -54,8 +56,8 @@ impl ViewportDesc {
         self.surface.configure(device, &config);
 
         let platform = Platform::new(PlatformDescriptor {
-            physical_width: size.width as u32,
-            physical_height: size.height as u32,
+            physical_width: size.width,
+            physical_height: size.height,
             scale_factor: self.window.scale_factor(),
             font_definitions: FontDefinitions::default(),
             style: Default::default(),